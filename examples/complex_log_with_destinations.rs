@@ -123,6 +123,7 @@ mod mod_two {
     use log_x::Logger;
     use log_x::loggers::log_levels::LogLevel;
     use log_x::loggers::mod_logger::{ModLogger, ModuleLoggerTrait};
+    use log_x::output::format::LogFormat;
 
     pub fn log_something() {
         let this_module = module_path!();
@@ -131,6 +132,8 @@ mod mod_two {
         Logger::set_mod_logging(this_module, LogLevel::Info, false);
         // setting the log output to file
         Logger::set_mod_log_to_file(this_module, "examples/log_mod_two_log.txt");
+        // writing one JSON object per line in the file
+        Logger::set_mod_file_format(this_module, LogFormat::Json);
 
         println!("\n{:-<200}", "");
         println!(
@@ -0,0 +1,31 @@
+#[macro_use]
+extern crate log_x;
+
+use log_x::Logger;
+use log_x::integrations::log_bridge;
+use log_x::loggers::global_logger::DefaultLoggerTrait;
+use log_x::loggers::log_levels::LogLevel;
+use log_x::loggers::mod_logger::ModuleLoggerTrait;
+
+fn main() {
+    // Set the default log level to Info
+    Logger::set_log_level(LogLevel::Info);
+
+    // route the `log` crate facade into log_x
+    log_bridge::init().expect("a global logger is already installed");
+
+    // records from the `log` crate and from log_x macros share the same configuration
+    log::info!("This is an info message from the log crate");
+    log_info!("This is an info message from log_x");
+
+    // structured key-values are kept as fields
+    log::info!(user_id = 42, cached = true; "This is an info message with key-values from the log crate");
+
+    // below messages will not be printed as the log level is set to Info
+    log::debug!("This is a debug message from the log crate");
+    log_debug!("This is a debug message from log_x");
+
+    // module overrides also apply to records emitted by other crates through `log`
+    Logger::set_mod_logging("third_party::db", LogLevel::Trace, true);
+    log::trace!(target: "third_party::db", "This is a trace message from a third party crate");
+}
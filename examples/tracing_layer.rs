@@ -0,0 +1,33 @@
+use log_x::Logger;
+use log_x::integrations::tracing_layer::LogxLayer;
+use log_x::loggers::global_logger::DefaultLoggerTrait;
+use log_x::loggers::log_levels::LogLevel;
+use log_x::loggers::mod_logger::ModuleLoggerTrait;
+use tracing_subscriber::layer::SubscriberExt;
+
+fn main() {
+    // Set the default log level to Info
+    Logger::set_log_level(LogLevel::Info);
+
+    // write tracing events through the log_x destinations
+    let subscriber = tracing_subscriber::registry().with(LogxLayer::new());
+    tracing::subscriber::set_global_default(subscriber).expect("a global subscriber is already installed");
+
+    tracing::info!("This is an info event without span");
+
+    // span names and fields are rendered in front of the message
+    let request = tracing::info_span!("request", id = 42, path = "/login");
+    let _request = request.enter();
+    tracing::warn!(user = "alice", "This is a warning event inside a span");
+
+    let db = tracing::info_span!("db", table = "users");
+    let _db = db.enter();
+    tracing::info!(rows = 3, "This is an info event inside nested spans");
+
+    // below event will not be printed as the log level is set to Info
+    tracing::debug!("This is a debug event");
+
+    // module overrides are honoured as well
+    Logger::set_mod_logging(module_path!(), LogLevel::Trace, true);
+    tracing::trace!("This is a trace event, printed thanks to the module override");
+}
@@ -0,0 +1,166 @@
+//! This module defines the structured key-value fields attached to a [`LogMetadata`](crate::LogMetadata).
+//!
+//! Fields keep their type until they reach a destination, so structured outputs receive them intact
+//! while the stdout and file outputs render them as `key=value` after the log message.
+//!
+//! # Structures
+//!
+//! - `Field`: A key and its value.
+//!
+//! # Enums
+//!
+//! - `FieldValue`: A typed field value: string, integer, float, boolean, or a value captured through
+//!   its `Display` or `Debug` implementation.
+//!
+//! # Usage
+//!
+//! Fields are usually attached with the logging macros, placed before the message. Values are
+//! converted with `FieldValue::from`, `%value` captures a value with `Display` and `?value` with `Debug`:
+//!
+//! ```
+//! use log_x::log_info;
+//!
+//! let peer = std::net::Ipv4Addr::LOCALHOST;
+//! log_info!(user_id = 42, request_id = "abc", "login ok");
+//! log_info!(peer = %peer, attempts = ?Some(3), "connection accepted from {}", "localhost");
+//! log_info!(elapsed_ms = 12.5, cached = true);
+//! ```
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// A structured key-value field attached to a log entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    key: String,
+    value: FieldValue,
+}
+
+impl Field {
+    /// Creates a new field with the given key and value.
+    pub fn new(key: impl Into<String>, value: impl Into<FieldValue>) -> Self {
+        Field {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Returns the key of the field.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the value of the field.
+    pub fn value(&self) -> &FieldValue {
+        &self.value
+    }
+}
+
+/// Formats the field as `key=value`.
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.key, self.value)
+    }
+}
+
+/// A typed field value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Str(String),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+    /// A value captured through its `Display` implementation.
+    Display(String),
+    /// A value captured through its `Debug` implementation.
+    Debug(String),
+}
+
+impl FieldValue {
+    /// Captures a value through its `Display` implementation.
+    pub fn display(value: &impl Display) -> Self {
+        FieldValue::Display(value.to_string())
+    }
+
+    /// Captures a value through its `Debug` implementation.
+    pub fn debug(value: &impl Debug) -> Self {
+        FieldValue::Debug(format!("{:?}", value))
+    }
+}
+
+/// Formats the value for text outputs. Strings that are empty or contain whitespace, quotes or `=`
+/// are quoted so the `key=value` pairs stay unambiguous.
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Str(value) | FieldValue::Display(value) | FieldValue::Debug(value) => {
+                if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+                    write!(f, "{:?}", value)
+                } else {
+                    write!(f, "{}", value)
+                }
+            }
+            FieldValue::I64(value) => write!(f, "{}", value),
+            FieldValue::U64(value) => write!(f, "{}", value),
+            FieldValue::F64(value) => write!(f, "{}", value),
+            FieldValue::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::Str(value)
+    }
+}
+
+impl From<&String> for FieldValue {
+    fn from(value: &String) -> Self {
+        FieldValue::Str(value.clone())
+    }
+}
+
+impl From<char> for FieldValue {
+    fn from(value: char) -> Self {
+        FieldValue::Str(value.to_string())
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+impl From<f32> for FieldValue {
+    fn from(value: f32) -> Self {
+        FieldValue::F64(value.into())
+    }
+}
+
+impl From<f64> for FieldValue {
+    fn from(value: f64) -> Self {
+        FieldValue::F64(value)
+    }
+}
+
+// Implement From for the integer types, signed ones are stored as i64 and unsigned ones as u64
+macro_rules! impl_from_integer {
+    ($variant:ident, $target:ty, $($source:ty),+) => {
+        $(
+            impl From<$source> for FieldValue {
+                fn from(value: $source) -> Self {
+                    FieldValue::$variant(value as $target)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_integer!(I64, i64, i8, i16, i32, i64, isize);
+impl_from_integer!(U64, u64, u8, u16, u32, u64, usize);
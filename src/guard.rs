@@ -0,0 +1,106 @@
+//! This module ensures the log records are written before the application exits.
+//!
+//! # Structures
+//!
+//! - `LogGuard`: Shuts the logger down when dropped: writes the records queued by the asynchronous
+//!   mode, flushes stdout and the log files, and waits for the rotated files being compressed.
+//!
+//! # Functions
+//!
+//! - [`install_panic_hook`]: Logs the panics at `Error` level before running the previous panic hook.
+//!
+//! The guard is returned by [`Logger::init`](crate::Logger::init) and
+//! [`Logger::start_async`](crate::Logger::start_async), and should be kept alive until the end of
+//! `main`. As it is dropped while unwinding, the records are also written when `main` panics.
+//!
+//! ```
+//! use log_x::Logger;
+//! use log_x::loggers::global_logger::DefaultLoggerTrait;
+//! use log_x::loggers::log_levels::LogLevel;
+//!
+//! let _guard = Logger::init();
+//! log_x::guard::install_panic_hook();
+//! Logger::set_log_level(LogLevel::Info);
+//!
+//! log_x::log_info!("written to disk before main returns");
+//! ```
+use std::backtrace::Backtrace;
+use std::panic::{self, PanicHookInfo};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crate::loggers::log_levels::LogLevel;
+use crate::output::timestamp::Timestamp;
+use crate::{LogMetadata, Logger};
+
+/// Whether the panic hook is installed, so that it is never chained to itself.
+static PANIC_HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Shuts the logger down when dropped, see [`Logger::shutdown`].
+#[must_use = "the logger is shut down as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct LogGuard {
+    _private: (),
+}
+
+impl LogGuard {
+    /// Creates a guard, the logger is shut down when it is dropped.
+    pub(crate) fn new() -> Self {
+        LogGuard { _private: () }
+    }
+}
+
+impl Drop for LogGuard {
+    fn drop(&mut self) {
+        Logger::shutdown();
+    }
+}
+
+/// Installs a panic hook logging the panic message, location and backtrace at `Error` level
+/// through the destinations of the default logger, or of the module logger of the `panic` module,
+/// then running the previous panic hook. Installing it again does nothing.
+pub fn install_panic_hook() {
+    if PANIC_HOOK_INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        log_panic(info);
+        previous(info);
+    }));
+}
+
+/// Logs the panic and writes it to its destinations right away, the process may abort afterwards.
+fn log_panic(info: &PanicHookInfo<'_>) {
+    let payload = info.payload();
+    let message = match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+        (Some(message), _) => message,
+        (_, Some(message)) => message.as_str(),
+        _ => "Box<dyn Any>",
+    };
+    let (file, line, column) = match info.location() {
+        Some(location) => (location.file(), location.line(), location.column()),
+        None => ("<unknown>", 0, 0),
+    };
+    let thread = thread::current();
+    let message = format!(
+        "thread '{}' panicked at {}:{}:{}: {}\nstack backtrace:\n{}",
+        thread.name().unwrap_or("<unnamed>"),
+        file,
+        line,
+        column,
+        message,
+        Backtrace::force_capture()
+    );
+
+    Logger::log(&mut LogMetadata::new(
+        Timestamp::now(),
+        LogLevel::Error,
+        file,
+        "panic",
+        line,
+        message,
+    ));
+    Logger::flush();
+}
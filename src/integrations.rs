@@ -0,0 +1,5 @@
+#[cfg(feature = "log")]
+pub mod log_bridge;
+
+#[cfg(feature = "tracing")]
+pub mod tracing_layer;
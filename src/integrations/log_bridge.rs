@@ -0,0 +1,179 @@
+//! Bridge between the [`log`](https://docs.rs/log) crate facade and `log_x`.
+//!
+//! Most of the ecosystem emits records through `log::info!`, `log::warn!` and friends. This module
+//! provides [`LogxLogger`], an implementation of [`log::Log`] built on top of [`Logger::enabled`] and
+//! [`Logger::log`], so those records go through the same `DEFAULT_LOGGER` and `MODULES_LOGGER`
+//! configuration as the `log_x` macros.
+//!
+//! The `target` of a `log::Record` is used as the module name. By default `log` sets the target to
+//! `module_path!()`, so module overrides configured with `set_mod_logging` also apply to
+//! third-party crates, e.g. `Logger::set_mod_logging("hyper", LogLevel::Warn, false)`.
+//! Structured key-values of a record (`log::info!(user_id = 42; "login ok")`) are attached to the
+//! `LogMetadata` as typed [`Field`]s.
+//!
+//! This module is only available with the `log` cargo feature enabled.
+//!
+//! # Usage
+//!
+//! ```
+//! use log_x::Logger;
+//! use log_x::integrations::log_bridge;
+//! use log_x::loggers::global_logger::DefaultLoggerTrait;
+//! use log_x::loggers::log_levels::LogLevel;
+//!
+//! Logger::set_log_level(LogLevel::Info);
+//! log_bridge::init().expect("another logger is already installed");
+//!
+//! log::info!("This record is written by log_x");
+//! ```
+//!
+//! # Level mapping
+//!
+//! | `log`                | `log_x`            |
+//! |----------------------|--------------------|
+//! | `LevelFilter::Off`   | `LogLevel::Off`    |
+//! | `Level::Error`       | `LogLevel::Error`  |
+//! | `Level::Warn`        | `LogLevel::Warn`   |
+//! | `Level::Info`        | `LogLevel::Info`   |
+//! | `Level::Debug`       | `LogLevel::Debug`  |
+//! | `Level::Trace`       | `LogLevel::Trace`  |
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::kv::{Error as KvError, Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::fields::{Field, FieldValue};
+use crate::loggers::log_levels::LogLevel;
+use crate::output::timestamp::Timestamp;
+use crate::{LogMetadata, Logger};
+
+/// The logger instance registered with the `log` crate by [`init`].
+static LOGX_LOGGER: LogxLogger = LogxLogger;
+
+/// Whether [`LOGX_LOGGER`] is installed, so that the `log` max level is only set for it.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// An implementation of [`log::Log`] that forwards every record to [`Logger`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogxLogger;
+
+impl Log for LogxLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        Logger::is_enabled(metadata.level().into(), metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        // Skip formatting the arguments of the disabled records
+        let Some(config) = Logger::resolve(record.level().into(), record.target()) else {
+            return;
+        };
+
+        let mut metadata = LogMetadata::new(
+            Timestamp::now(),
+            record.level().into(),
+            record.file().unwrap_or("<unknown>"),
+            record.target(),
+            record.line().unwrap_or(0),
+            record.args().to_string(),
+        );
+
+        let mut visitor = FieldVisitor::default();
+        // The visitor never fails
+        let _ = record.key_values().visit(&mut visitor);
+        metadata.fields = visitor.fields;
+
+        Logger::log_with(metadata, config);
+    }
+
+    fn flush(&self) {
+        Logger::flush();
+    }
+}
+
+/// Collects the key-values of a record as typed fields.
+#[derive(Default)]
+struct FieldVisitor {
+    fields: Vec<Field>,
+}
+
+impl<'kvs> VisitSource<'kvs> for FieldVisitor {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), KvError> {
+        let value = if let Some(value) = value.to_bool() {
+            FieldValue::Bool(value)
+        } else if let Some(value) = value.to_i64() {
+            FieldValue::I64(value)
+        } else if let Some(value) = value.to_u64() {
+            FieldValue::U64(value)
+        } else if let Some(value) = value.to_f64() {
+            FieldValue::F64(value)
+        } else if let Some(value) = value.to_borrowed_str() {
+            FieldValue::Str(value.to_string())
+        } else {
+            FieldValue::Display(value.to_string())
+        };
+        self.fields.push(Field::new(key.as_str(), value));
+        Ok(())
+    }
+}
+
+/// Installs [`LogxLogger`] as the global logger of the `log` crate.
+///
+/// The `log` max level is kept in sync with the most verbose level written by any logger, see
+/// [`crate::loggers::snapshot::max_level`], so that the `log` macros skip the disabled records
+/// before reaching [`Logger::enabled`], which applies the default and per-module levels.
+///
+/// # Errors
+///
+/// Returns an error if a global logger has already been installed.
+pub fn init() -> Result<(), SetLoggerError> {
+    log::set_logger(&LOGX_LOGGER)?;
+    INSTALLED.store(true, Ordering::Release);
+    log::set_max_level(crate::loggers::snapshot::max_level().into());
+    Ok(())
+}
+
+/// Sets the `log` max level, if [`LogxLogger`] is installed. Called with `Trace` while the
+/// configuration changes, then with the most verbose level written by any logger.
+pub(crate) fn sync_max_level(level: LogLevel) {
+    if INSTALLED.load(Ordering::Acquire) {
+        log::set_max_level(level.into());
+    }
+}
+
+impl From<Level> for LogLevel {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Error => LogLevel::Error,
+            Level::Warn => LogLevel::Warn,
+            Level::Info => LogLevel::Info,
+            Level::Debug => LogLevel::Debug,
+            Level::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+impl From<LevelFilter> for LogLevel {
+    fn from(level: LevelFilter) -> Self {
+        match level {
+            LevelFilter::Off => LogLevel::Off,
+            LevelFilter::Error => LogLevel::Error,
+            LevelFilter::Warn => LogLevel::Warn,
+            LevelFilter::Info => LogLevel::Info,
+            LevelFilter::Debug => LogLevel::Debug,
+            LevelFilter::Trace => LogLevel::Trace,
+        }
+    }
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
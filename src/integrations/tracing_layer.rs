@@ -0,0 +1,210 @@
+//! A [`tracing_subscriber::Layer`] that writes `tracing` events through `log_x`.
+//!
+//! [`LogxLayer`] converts every `tracing` event into a [`LogMetadata`] and hands it to the same
+//! destinations (stdout, file) used by the `log_x` macros, through the queue of the asynchronous mode
+//! when it is started. Level filtering follows the `DEFAULT_LOGGER` and `MODULES_LOGGER`
+//! configuration, using the event target as the module name.
+//!
+//! Span context is rendered in front of the message, from the outermost span to the innermost one,
+//! together with the span fields, e.g. `request{id=42}:db{table="users"}: query done rows=3`.
+//! Event fields are attached to the `LogMetadata` as typed [`Field`](crate::fields::Field)s.
+//!
+//! This module is only available with the `tracing` cargo feature enabled.
+//!
+//! # Usage
+//!
+//! ```
+//! use log_x::Logger;
+//! use log_x::integrations::tracing_layer::LogxLayer;
+//! use log_x::loggers::global_logger::DefaultLoggerTrait;
+//! use log_x::loggers::log_levels::LogLevel;
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! Logger::set_log_level(LogLevel::Info);
+//! let subscriber = tracing_subscriber::registry().with(LogxLayer::new());
+//!
+//! tracing::subscriber::with_default(subscriber, || {
+//!     let span = tracing::info_span!("request", id = 42);
+//!     let _enter = span.enter();
+//!     tracing::info!(rows = 3, "query done");
+//! });
+//! ```
+use std::fmt::{self, Write};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::fields::{Field as LogField, FieldValue};
+use crate::loggers::log_levels::LogLevel;
+use crate::output::timestamp::Timestamp;
+use crate::{LogMetadata, Logger};
+
+/// A `tracing_subscriber` layer that writes events through `log_x` destinations.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogxLayer;
+
+impl LogxLayer {
+    /// Creates a new `LogxLayer`.
+    pub fn new() -> Self {
+        LogxLayer
+    }
+}
+
+/// The rendered fields of a span, stored in the span extensions.
+struct SpanFields(String);
+
+/// Collects the fields of a span rendered as `key=value` pairs.
+#[derive(Default)]
+struct SpanVisitor {
+    fields: String,
+}
+
+impl Visit for SpanVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &value);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        let _ = write!(self.fields, "{}={:?}", field.name(), value);
+    }
+}
+
+/// Collects the fields of an event as typed fields.
+/// The `message` field is kept apart as the log message.
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: Vec<LogField>,
+}
+
+impl EventVisitor {
+    fn push(&mut self, field: &Field, value: FieldValue) {
+        self.fields.push(LogField::new(field.name(), value));
+    }
+}
+
+impl Visit for EventVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.push(field, value.into());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.push(field, FieldValue::debug(&value));
+        }
+    }
+}
+
+impl<S> Layer<S> for LogxLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut visitor = SpanVisitor::default();
+            attrs.record(&mut visitor);
+            span.extensions_mut().insert(SpanFields(visitor.fields));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut visitor = SpanVisitor::default();
+            values.record(&mut visitor);
+            let mut extensions = span.extensions_mut();
+            match extensions.get_mut::<SpanFields>() {
+                Some(SpanFields(fields)) if !fields.is_empty() => {
+                    fields.push(' ');
+                    fields.push_str(&visitor.fields);
+                }
+                Some(SpanFields(fields)) => *fields = visitor.fields,
+                None => extensions.insert(SpanFields(visitor.fields)),
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let event_metadata = event.metadata();
+        let level = (*event_metadata.level()).into();
+        // Skip rendering the span context and the fields for disabled records
+        let Some(config) = Logger::resolve(level, event_metadata.target()) else {
+            return;
+        };
+        let timestamp = Timestamp::now();
+
+        let mut spans = String::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                spans.push_str(span.name());
+                if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>()
+                    && !fields.is_empty()
+                {
+                    let _ = write!(spans, "{{{}}}", fields);
+                }
+                spans.push(':');
+            }
+        }
+
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+
+        let mut message = spans;
+        if !message.is_empty() {
+            message.push(' ');
+        }
+        message.push_str(&visitor.message);
+
+        let mut metadata = LogMetadata::new(
+            timestamp,
+            level,
+            event_metadata.file().unwrap_or("<unknown>"),
+            event_metadata.target(),
+            event_metadata.line().unwrap_or(0),
+            message,
+        );
+        metadata.fields = visitor.fields;
+        Logger::log_with(metadata, config);
+    }
+}
+
+impl From<Level> for LogLevel {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            // Level::TRACE
+            _ => LogLevel::Trace,
+        }
+    }
+}
@@ -1,6 +1,9 @@
 #![doc = include_str!("../README.md")]
 
 // Import necessary items
+pub mod fields;
+pub mod guard;
+pub mod integrations;
 pub mod loggers;
 pub mod output;
 pub mod terminal;
@@ -10,11 +13,20 @@ pub mod macros;
 
 use std::fmt::{Debug, Display};
 use std::io::Write;
+use std::sync::{Arc, LazyLock};
 
+use chrono::{DateTime, Local};
+
+use fields::Field;
+use guard::LogGuard;
+use loggers::filter::{Directives, FilterError};
 use loggers::global_logger::{DefaultLogger, DefaultLoggerTrait};
 use loggers::log_levels::LogLevel;
 use loggers::mod_logger::{ModLogger, ModuleLoggerTrait};
+use loggers::snapshot::LoggerConfig;
+use output::async_writer::OverflowPolicy;
 use output::logdest::{LogDestination, log_to_destination};
+use output::timestamp::Timestamp;
 use terminal::colors::Colorize;
 
 // Implement the Colorize trait for all types that implement Display and Debug
@@ -35,23 +47,28 @@ pub trait LogxTrait {
 /// A structure representing log metadata.
 pub struct LogMetadata {
     /// The timestamp when the log entry was created.
-    timestamp: String,
+    timestamp: Timestamp,
     /// The severity level of the log entry.
     level: LogLevel,
     /// The file where the log entry was generated.
     file: String,
     /// The module where the log entry was generated.
     module: String,
-    /// logging from the module
-    logging_from_module: bool,
+    /// The paranoia setting of the logger the log entry is logged from.
+    paranoia: bool,
     /// The line number in the file where the log entry was generated.
     line: u32,
     /// The log message.
     message: String,
-    /// The log destinations.
-    log_destinations: LogDestination,
+    /// The structured key-value fields.
+    fields: Vec<Field>,
+    /// The log destinations, shared with the configuration of the logger.
+    log_destinations: Arc<LogDestination>,
 }
 
+/// The destinations of the log entries not resolved by a logger yet.
+static UNRESOLVED_DESTINATION: LazyLock<Arc<LogDestination>> = LazyLock::new(|| Arc::new(LogDestination::default()));
+
 /// A structure representing metadata for a log entry.
 ///
 /// # Fields
@@ -61,20 +78,24 @@ pub struct LogMetadata {
 /// - `module`: The module where the log entry was generated.
 /// - `line`: The line number in the file where the log entry was generated.
 /// - `message`: The log message.
+/// - `fields`: The structured key-value fields.
 ///
 /// # Methods
 /// - `new`: Creates a new `LogMetadata` instance.
+/// - `with_fields`: Attaches structured key-value fields to the log entry.
 /// - `level`: Returns the severity level of the log entry.
 /// - `module`: Returns the module where the log entry was generated.
 /// - `message`: Returns the log message.
+/// - `fields`: Returns the structured key-value fields.
 /// - `file`: Returns the file where the log entry was generated.
 /// - `line`: Returns the line number in the file where the log entry was generated.
-/// - `timestamp`: Returns the timestamp when the log entry was created.
-
+/// - `timestamp`: Returns the timestamp when the log entry was created, formatted for its destination.
+/// - `time`: Returns the instant when the log entry was created.
 impl LogMetadata {
-    /// Creates a new `LogMetadata` instance with the given values.
+    /// Creates a new `LogMetadata` instance with the given values. The timestamp is either an instant,
+    /// e.g. `Timestamp::now()`, formatted by each destination, or a preformatted text, e.g. `timestamp!()`.
     pub fn new(
-        timestamp: impl Into<String>,
+        timestamp: impl Into<Timestamp>,
         level: LogLevel,
         file: impl Into<String>,
         module: impl Into<String>,
@@ -86,13 +107,26 @@ impl LogMetadata {
             level,
             file: file.into(),
             module: module.into(),
-            logging_from_module: false,
+            paranoia: false,
             line,
             message: message.into(),
-            log_destinations: LogDestination::default(),
+            fields: Vec::new(),
+            log_destinations: UNRESOLVED_DESTINATION.clone(),
         }
     }
 
+    /// Attaches the configuration of the logger the log entry is logged from.
+    fn with_config(&mut self, config: LoggerConfig) {
+        self.paranoia = config.paranoia();
+        self.log_destinations = config.destination().clone();
+    }
+
+    /// Attaches structured key-value fields to the log entry, replacing the existing ones.
+    pub fn with_fields(mut self, fields: Vec<Field>) -> Self {
+        self.fields = fields;
+        self
+    }
+
     /// Returns the severity level of the log entry.
     pub fn level(&self) -> LogLevel {
         self.level
@@ -108,6 +142,11 @@ impl LogMetadata {
         &self.message
     }
 
+    /// Returns the structured key-value fields of the log entry.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
     /// Returns the file where the log entry was generated.
     pub fn file(&self) -> &str {
         &self.file
@@ -118,42 +157,176 @@ impl LogMetadata {
         self.line
     }
 
-    /// Returns the timestamp when the log entry was created.
-    pub fn timestamp(&self) -> &str {
-        &self.timestamp
+    /// Returns the timestamp when the log entry was created, formatted with the timestamp format of
+    /// its destination.
+    pub fn timestamp(&self) -> String {
+        self.timestamp.format(
+            self.log_destinations.timestamp_format(),
+            self.log_destinations.timestamp_utc(),
+        )
+    }
+
+    /// Returns the instant when the log entry was created.
+    pub fn time(&self) -> DateTime<Local> {
+        self.timestamp.time()
     }
 }
 
 pub struct Logger {}
 
 impl Logger {
-    /// Checks if logging is enabled for the given log metadata.
+    /// Checks if logging is enabled for the given log metadata: the level of the logger enables it and
+    /// at least one of its destinations accepts it. Attaches the configuration of the logger to the
+    /// metadata if enabled.
     pub fn enabled(metadata: &mut LogMetadata) -> bool {
-        let module_logger = ModLogger::get(metadata.module.as_str());
-        let default_level = DefaultLogger::log_level();
-        if let Some(module_logger) = module_logger {
-            metadata.logging_from_module = true;
-            metadata.log_destinations = module_logger.log_destinations;
-            return metadata.level <= module_logger.log_level;
+        match Logger::resolve(metadata.level, &metadata.module) {
+            Some(config) => {
+                metadata.with_config(config);
+                true
+            }
+            None => false,
         }
+    }
+
+    /// Checks if a record of the given level logged from the given module would be written, before
+    /// building it.
+    pub fn is_enabled(level: LogLevel, module: &str) -> bool {
+        Logger::resolve(level, module).is_some()
+    }
 
-        metadata.log_destinations = DefaultLogger::log_destination();
-        metadata.level <= default_level
+    /// Returns the configuration of the logger a record of the given level logged from the given
+    /// module is written by, `None` if the record is disabled. Does not take any lock unless the
+    /// configuration changed, see [`loggers::snapshot`]: the log macros resolve the logger once,
+    /// before capturing the timestamp and formatting the message, then call [`Logger::log_with`].
+    pub fn resolve(level: LogLevel, module: &str) -> Option<LoggerConfig> {
+        loggers::snapshot::resolve_config(level, module)
     }
 
     /// Logs the given log metadata.
     pub fn log(metadata: &mut LogMetadata) {
         if Logger::enabled(metadata) {
+            Logger::write(metadata);
+        }
+    }
+
+    /// Logs the given log metadata with the configuration of its logger, returned by
+    /// [`Logger::resolve`] for the same level and module.
+    pub fn log_with(mut metadata: LogMetadata, config: LoggerConfig) {
+        metadata.with_config(config);
+        Logger::write(&metadata);
+    }
+
+    /// Queues the metadata in the asynchronous mode, or writes it to its destinations.
+    fn write(metadata: &LogMetadata) {
+        if !output::async_writer::enqueue(metadata) {
             log_to_destination(metadata);
         }
     }
 
-    /// Flushes the log output.
+    /// Returns a guard shutting the logger down when dropped: the queued records are written and the
+    /// log output is flushed before the application exits, see [`guard`].
+    pub fn init() -> LogGuard {
+        LogGuard::new()
+    }
+
+    /// Starts the asynchronous mode: records are queued and written by a background thread, see
+    /// [`output::async_writer`]. Updates the capacity and overflow policy if already started.
+    /// Returns a guard shutting the logger down when dropped, see [`guard`].
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of queued records.
+    /// * `overflow` - What to do with a record logged while the queue is full.
+    pub fn start_async(capacity: usize, overflow: OverflowPolicy) -> LogGuard {
+        output::async_writer::start(capacity, overflow);
+        LogGuard::new()
+    }
+
+    /// Returns the number of records dropped by the asynchronous mode because the queue was full.
+    pub fn dropped_records() -> u64 {
+        output::async_writer::dropped()
+    }
+
+    /// Stops the asynchronous mode after writing the queued records, then flushes the log output and
+    /// waits for the rotated files being compressed. Returns the number of records dropped because the
+    /// queue was full, also printed to stderr.
+    pub fn shutdown() -> u64 {
+        let dropped = output::async_writer::shutdown();
+        Logger::flush();
+        #[cfg(feature = "compression")]
+        output::compression::wait_idle();
+        dropped
+    }
+
+    /// Configures the default and module log levels from the filter directives stored in the
+    /// environment variable `var`, e.g. `LOGX="warn,my_crate::db=trace"`.
+    /// Does nothing if the variable is not set. See [`loggers::filter`] for the syntax.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without applying any directive, if the filter is malformed.
+    pub fn init_from_env(var: &str) -> Result<(), FilterError> {
+        match std::env::var(var) {
+            Ok(filter) => {
+                Directives::parse(&filter)?.apply();
+                Ok(())
+            }
+            Err(std::env::VarError::NotPresent) => Ok(()),
+            Err(std::env::VarError::NotUnicode(_)) => Err(FilterError::NotUnicode { var: var.to_string() }),
+        }
+    }
+
+    /// Configures the default and module loggers from the TOML file at `path`, replacing their current
+    /// configuration. See [`loggers::config`] for the keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without changing the configuration, if the file cannot be read or is invalid.
+    #[cfg(feature = "config")]
+    pub fn init_from_file(path: impl AsRef<std::path::Path>) -> Result<(), loggers::config::ConfigError> {
+        loggers::config::LogConfig::load(path)?.apply();
+        Ok(())
+    }
+
+    /// Configures the default and module loggers from the TOML file at `path`, then reloads it every
+    /// time it changes, polling it every `interval`, until the returned watcher is dropped. See
+    /// [`loggers::reload`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without changing the configuration, if the file cannot be read or is invalid.
+    #[cfg(feature = "config")]
+    pub fn watch_config(
+        path: impl AsRef<std::path::Path>,
+        interval: std::time::Duration,
+    ) -> Result<loggers::reload::ConfigWatcher, loggers::config::ConfigError> {
+        loggers::reload::ConfigWatcher::start(path, interval)
+    }
+
+    /// Enables or disables the buffering of the log files, disabled by default: every record is then
+    /// written to its file right away. See [`output::file_writer`].
+    ///
+    /// **Warning**: with buffering enabled, the records still buffered when the process exits are
+    /// lost. Keep the guard returned by [`Logger::init`] alive until the end of `main`, or call
+    /// [`Logger::flush`] before exiting.
+    pub fn set_file_buffering(buffered: bool) {
+        output::file_writer::set_buffered(buffered);
+    }
+
+    /// Flushes the log output: waits for the queued records to be written, then flushes stdout, the
+    /// sinks of all the loggers and the buffers of all the open log files. Must be called before the
+    /// application exits when the log files are buffered, see [`output::file_writer`].
     pub fn flush() {
+        output::async_writer::wait_idle();
         match std::io::stdout().flush() {
             Ok(_) => {}
             Err(e) => eprintln!("Failed to flush stdout: {:?}", e),
         }
+        DefaultLogger::log_destination().flush();
+        for destination in ModLogger::all_log_destinations() {
+            destination.flush();
+        }
+        output::file_writer::flush_all();
     }
 }
 
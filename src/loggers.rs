@@ -1,3 +1,10 @@
+pub mod callsite;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod filter;
 pub mod global_logger;
 pub mod log_levels;
 pub mod mod_logger;
+#[cfg(feature = "config")]
+pub mod reload;
+pub mod snapshot;
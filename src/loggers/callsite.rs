@@ -0,0 +1,82 @@
+//! This module caches, for each log macro call, whether its records are enabled.
+//!
+//! Every expansion of the log macros declares a static [`Callsite`]: its level, module, file and
+//! line are known at compile time, so whether it is enabled only changes with the configuration.
+//! The callsite keeps the answer along with the [`generation`](crate::loggers::snapshot::generation)
+//! of the configuration it was computed for: a disabled callsite costs an atomic compare until
+//! `DefaultLogger` or `ModLogger` is reconfigured, which bumps the generation and makes every
+//! callsite check its level again on its next call.
+//!
+//! ```
+//! use log_x::loggers::callsite::Callsite;
+//! use log_x::loggers::log_levels::LogLevel;
+//!
+//! static CALLSITE: Callsite = Callsite::new(LogLevel::Trace, module_path!(), file!(), line!());
+//!
+//! // the default logger is `Off`
+//! assert!(!CALLSITE.is_enabled());
+//! ```
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Logger;
+use crate::loggers::log_levels::LogLevel;
+use crate::loggers::snapshot;
+
+/// A log macro call, with its cached interest.
+#[derive(Debug)]
+pub struct Callsite {
+    level: LogLevel,
+    module: &'static str,
+    file: &'static str,
+    line: u32,
+    /// The generation the interest was computed for, shifted left by one, and whether the callsite
+    /// is enabled in the lowest bit. `0` until computed, the generations start at 1.
+    interest: AtomicU64,
+}
+
+impl Callsite {
+    /// Creates a callsite logging at the given level from the given source location.
+    pub const fn new(level: LogLevel, module: &'static str, file: &'static str, line: u32) -> Self {
+        Callsite {
+            level,
+            module,
+            file,
+            line,
+            interest: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns whether the records of the callsite are enabled, checking the configuration only if it
+    /// changed since the last call.
+    pub fn is_enabled(&self) -> bool {
+        let generation = snapshot::generation();
+        let interest = self.interest.load(Ordering::Relaxed);
+        if interest >> 1 == generation {
+            return interest & 1 == 1;
+        }
+
+        let enabled = Logger::is_enabled(self.level, self.module);
+        self.interest.store(generation << 1 | enabled as u64, Ordering::Relaxed);
+        enabled
+    }
+
+    /// Returns the level of the callsite.
+    pub fn level(&self) -> LogLevel {
+        self.level
+    }
+
+    /// Returns the module of the callsite.
+    pub fn module(&self) -> &'static str {
+        self.module
+    }
+
+    /// Returns the file of the callsite.
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    /// Returns the line of the callsite.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+}
@@ -0,0 +1,703 @@
+//! This module loads the configuration of the default and module loggers from a TOML file.
+//!
+//! The `[default]` table configures the `DEFAULT_LOGGER`, and each `[modules."path"]` table the
+//! `ModLogger` of a module. Both accept the same keys, all optional:
+//!
+//! - `level`: the log level, `off`, `error`, `warn`, `info`, `debug` or `trace`. Defaults to `off`
+//!   for the default logger, and to the level of the default logger for the modules.
+//! - `paranoia`: whether the file and line are added to the records. Defaults to `false`.
+//! - `console`: the console streams, `stdout` (the default), `stderr`, `split` or `off`.
+//! - `console_level`: the most verbose level printed to the console. Defaults to `trace`.
+//! - `files`: the log files, as `{ path = "app.log", level = "warn" }` tables, `level` defaulting
+//!   to `trace`.
+//! - `file_format`: the format of the log files, `text` (the default) or `json`.
+//! - `template`: the template of the console and text file lines, see [`crate::output::template`].
+//! - `timestamp`: the format of the timestamps, `seconds` (the default), `millis`, `micros`,
+//!   `nanos`, `rfc3339`, or a custom `chrono` format containing `%`.
+//! - `utc`: whether the timestamps are written in UTC. Defaults to `false`.
+//! - `rotation`: the rotation of the log files, `never`, `size`, `hourly`, `daily` or `weekly`, see
+//!   [`crate::output::rotation`].
+//! - `max_size`: the size in bytes the files are rolled over at, required by `rotation = "size"`.
+//! - `keep`: the number of archives kept by `rotation = "size"`. Defaults to `5`.
+//! - `max_age`: the age the dated files are deleted at, with `hourly`, `daily` or `weekly` rotation,
+//!   as a number followed by `s`, `m`, `h`, `d` or `w`, e.g. `"30d"`.
+//! - `compress`: whether the rotated files are compressed with gzip, requires the `compression`
+//!   cargo feature.
+//!
+//! Unknown keys, invalid values and invalid module paths are reported with their line, column and
+//! key. Applying a configuration replaces the default logger and all the module loggers at once;
+//! the custom sinks attached in code are kept. Without `rotation` and `compress` keys, a logger keeps
+//! the rotation and compression of its log files set in code.
+//!
+//! This module is only available with the `config` cargo feature enabled.
+//!
+//! ```
+//! use log_x::loggers::config::LogConfig;
+//! use log_x::loggers::log_levels::LogLevel;
+//!
+//! let config: LogConfig = r#"
+//! [default]
+//! level = "info"
+//! console = "stderr"
+//!
+//! [modules."my_crate::db"]
+//! level = "trace"
+//! paranoia = true
+//! files = [{ path = "db.log" }]
+//! rotation = "daily"
+//! max_age = "30d"
+//! "#
+//! .parse()
+//! .unwrap();
+//! assert_eq!(config.default_settings().level(), Some(LogLevel::Info));
+//! assert_eq!(config.modules()["my_crate::db"].level(), Some(LogLevel::Trace));
+//!
+//! // apply the configuration to DEFAULT_LOGGER and MODULES_LOGGER
+//! config.apply();
+//!
+//! // unknown keys and invalid levels are reported with their position
+//! let error = "[default]\nlevel = \"verbose\"\n".parse::<LogConfig>().unwrap_err();
+//! assert_eq!(error.to_string(), "line 2, column 9, key default.level: unknown log level \"verbose\", expected one of: off, error, warn, info, debug, trace");
+//! ```
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::de::{self, Deserializer};
+
+use super::filter::is_module_path;
+use super::global_logger::{DEFAULT_LOGGER, DefaultLogger};
+use super::log_levels::LogLevel;
+use super::mod_logger::{MODULES_LOGGER, ModLogger};
+use super::snapshot;
+use crate::output::format::LogFormat;
+use crate::output::logdest::LogDestination;
+use crate::output::rotation::{RotationPeriod, RotationPolicy};
+use crate::output::template::Template;
+use crate::output::timestamp::TimestampFormat;
+
+/// A parsed logging configuration file.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogConfig {
+    #[serde(default, deserialize_with = "checked")]
+    default: LoggerSettings,
+    #[serde(default, deserialize_with = "checked_modules")]
+    modules: BTreeMap<ModulePath, LoggerSettings>,
+}
+
+/// The settings of the default logger or of a module logger.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoggerSettings {
+    #[serde(default, deserialize_with = "level")]
+    level: Option<LogLevel>,
+    #[serde(default)]
+    paranoia: bool,
+    #[serde(default)]
+    console: Console,
+    #[serde(default = "trace", deserialize_with = "required_level")]
+    console_level: LogLevel,
+    #[serde(default)]
+    files: Vec<FileSettings>,
+    #[serde(default)]
+    file_format: FileFormat,
+    #[serde(default, deserialize_with = "template")]
+    template: Option<Template>,
+    #[serde(default, deserialize_with = "timestamp")]
+    timestamp: TimestampFormat,
+    #[serde(default)]
+    utc: bool,
+    #[serde(default)]
+    rotation: Option<Rotation>,
+    #[serde(default, deserialize_with = "max_size")]
+    max_size: Option<u64>,
+    #[serde(default)]
+    keep: Option<usize>,
+    #[serde(default, deserialize_with = "max_age")]
+    max_age: Option<Duration>,
+    #[serde(default)]
+    compress: Option<bool>,
+}
+
+/// A log file of a logger.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileSettings {
+    path: String,
+    #[serde(default = "trace", deserialize_with = "required_level")]
+    level: LogLevel,
+}
+
+/// The console streams of a logger.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Console {
+    #[default]
+    Stdout,
+    Stderr,
+    Split,
+    Off,
+}
+
+/// The format of the log files of a logger.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FileFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// The rotation of the log files of a logger.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Rotation {
+    Never,
+    Size,
+    Hourly,
+    Daily,
+    Weekly,
+}
+
+/// The settings of a logger, checked for consistency once all their keys are read.
+struct Checked(LoggerSettings);
+
+/// A module path, checked to be identifiers separated by `::`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ModulePath(String);
+
+/// The error returned when a configuration file cannot be read or parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The file cannot be read.
+    Io {
+        /// The path of the file.
+        path: String,
+        /// The reason the file cannot be read.
+        message: String,
+    },
+    /// The file is not valid TOML, or does not describe a valid configuration.
+    Invalid {
+        /// The 1-based line of the error.
+        line: usize,
+        /// The 1-based column of the error.
+        column: usize,
+        /// The dotted path of the key in error, e.g. `modules."my_crate::db".level`, if any.
+        key: Option<String>,
+        /// What is invalid.
+        message: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, message } => write!(f, "cannot read {}: {}", path, message),
+            ConfigError::Invalid {
+                line,
+                column,
+                key: Some(key),
+                message,
+            } => write!(f, "line {}, column {}, key {}: {}", line, column, key, message),
+            ConfigError::Invalid {
+                line,
+                column,
+                key: None,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl LogConfig {
+    /// Parses a configuration from its TOML source.
+    pub fn parse(source: &str) -> Result<Self, ConfigError> {
+        toml::from_str(source).map_err(|e| {
+            let offset = e.span().map(|span| span.start).unwrap_or(0).min(source.len());
+            let before = &source[..offset];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map(|index| index + 1).unwrap_or(0) + 1;
+            ConfigError::Invalid {
+                line,
+                column,
+                key: key_at(source, offset),
+                message: e.message().to_string(),
+            }
+        })
+    }
+
+    /// Reads and parses the configuration file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(source) => LogConfig::parse(&source),
+            Err(e) => Err(ConfigError::Io {
+                path: path.display().to_string(),
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    /// Returns the settings of the default logger.
+    pub fn default_settings(&self) -> &LoggerSettings {
+        &self.default
+    }
+
+    /// Returns the settings of the module loggers, by module path.
+    pub fn modules(&self) -> BTreeMap<&str, &LoggerSettings> {
+        self.modules
+            .iter()
+            .map(|(module, settings)| (module.0.as_str(), settings))
+            .collect()
+    }
+
+    /// Describes the changes from this configuration to `new`, one line per change, e.g.
+    /// `module my_crate::db: level info -> trace`.
+    pub fn changes(&self, new: &LogConfig) -> Vec<String> {
+        let mut changes = Vec::new();
+        let (old_level, new_level) = (
+            self.default.level.unwrap_or_default(),
+            new.default.level.unwrap_or_default(),
+        );
+        describe(
+            &mut changes,
+            "default logger",
+            &self.default,
+            old_level,
+            &new.default,
+            new_level,
+        );
+
+        for path in self.modules.keys() {
+            if !new.modules.contains_key(path) {
+                changes.push(format!("module {}: removed", path.0));
+            }
+        }
+        for (path, settings) in &new.modules {
+            let level = settings.level.unwrap_or(new_level);
+            match self.modules.get(path) {
+                Some(previous) => describe(
+                    &mut changes,
+                    &format!("module {}", path.0),
+                    previous,
+                    previous.level.unwrap_or(old_level),
+                    settings,
+                    level,
+                ),
+                None => changes.push(format!("module {}: added with level {}", path.0, level)),
+            }
+        }
+        changes
+    }
+
+    /// Applies the configuration, replacing `DEFAULT_LOGGER` and `MODULES_LOGGER` at once.
+    pub fn apply(&self) {
+        let default_level = self.default.level.unwrap_or(LogLevel::Off);
+        match (DEFAULT_LOGGER.write(), MODULES_LOGGER.write()) {
+            (Ok(mut default_logger), Ok(mut modules_log_level)) => {
+                let mut log_destination = self.default.destination(Some(&default_logger.log_destination));
+                log_destination.keep_custom_sinks(&default_logger.log_destination);
+                *default_logger = DefaultLogger {
+                    default_logger: default_level,
+                    paranoia: self.default.paranoia,
+                    log_destination,
+                };
+
+                let mut modules = HashMap::new();
+                for (ModulePath(module), settings) in &self.modules {
+                    let previous = modules_log_level.get(module).map(|previous| &previous.log_destinations);
+                    let mut log_destinations = settings.destination(previous);
+                    if let Some(previous) = previous {
+                        log_destinations.keep_custom_sinks(previous);
+                    }
+                    let mod_logger = ModLogger {
+                        module: module.clone(),
+                        log_level: settings.level.unwrap_or(default_level),
+                        paranoia: settings.paranoia,
+                        log_destinations,
+                    };
+                    modules.insert(module.clone(), mod_logger);
+                }
+                *modules_log_level = modules;
+            }
+            (Err(e), _) => {
+                eprintln!("Failed to apply the configuration to DEFAULT_LOGGER: {e}");
+            }
+            (_, Err(e)) => {
+                eprintln!("Failed to apply the configuration to MODULES_LOGGER: {:?}", e);
+            }
+        }
+        snapshot::invalidate();
+    }
+}
+
+impl FromStr for LogConfig {
+    type Err = ConfigError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        LogConfig::parse(source)
+    }
+}
+
+impl Default for LoggerSettings {
+    /// The settings of a table without any key: logging everything accepted by the level to stdout.
+    fn default() -> Self {
+        LoggerSettings {
+            level: None,
+            paranoia: false,
+            console: Console::Stdout,
+            console_level: LogLevel::Trace,
+            files: Vec::new(),
+            file_format: FileFormat::Text,
+            template: None,
+            timestamp: TimestampFormat::Seconds,
+            utc: false,
+            rotation: None,
+            max_size: None,
+            keep: None,
+            max_age: None,
+            compress: None,
+        }
+    }
+}
+
+impl LoggerSettings {
+    /// Returns the log level, if set.
+    pub fn level(&self) -> Option<LogLevel> {
+        self.level
+    }
+
+    /// Returns the paranoia setting.
+    pub fn paranoia(&self) -> bool {
+        self.paranoia
+    }
+
+    /// Returns the rotation policy of the log files, `None` if not set.
+    pub fn rotation(&self) -> Option<RotationPolicy> {
+        let time = |period| RotationPolicy::Time {
+            period,
+            pattern: None,
+            max_age: self.max_age,
+        };
+        match self.rotation? {
+            Rotation::Never => Some(RotationPolicy::Never),
+            Rotation::Size => Some(RotationPolicy::Size {
+                max_bytes: self.max_size.unwrap_or_default(),
+                keep: self.keep.unwrap_or(5),
+            }),
+            Rotation::Hourly => Some(time(RotationPeriod::Hourly)),
+            Rotation::Daily => Some(time(RotationPeriod::Daily)),
+            Rotation::Weekly => Some(time(RotationPeriod::Weekly)),
+        }
+    }
+
+    /// Returns whether the rotated log files are compressed, `None` if not set.
+    pub fn compress(&self) -> Option<bool> {
+        self.compress
+    }
+
+    /// Returns why the keys of the settings are inconsistent, if they are.
+    fn check(&self) -> Result<(), String> {
+        let time = matches!(
+            self.rotation,
+            Some(Rotation::Hourly | Rotation::Daily | Rotation::Weekly)
+        );
+        if self.rotation == Some(Rotation::Size) && self.max_size.is_none() {
+            return Err("rotation = \"size\" requires max_size".to_string());
+        }
+        if self.rotation != Some(Rotation::Size) && (self.max_size.is_some() || self.keep.is_some()) {
+            return Err("max_size and keep require rotation = \"size\"".to_string());
+        }
+        if !time && self.max_age.is_some() {
+            return Err("max_age requires rotation = \"hourly\", \"daily\" or \"weekly\"".to_string());
+        }
+        if cfg!(not(feature = "compression")) && self.compress == Some(true) {
+            return Err("compress requires the `compression` cargo feature".to_string());
+        }
+        Ok(())
+    }
+
+    /// Builds the destinations of the logger, keeping the rotation and compression of the `previous`
+    /// destinations when the settings do not set them.
+    fn destination(&self, previous: Option<&LogDestination>) -> LogDestination {
+        let mut destination = LogDestination::new(false, None);
+        let rotation = self
+            .rotation()
+            .or_else(|| previous.map(|previous| previous.file_rotation().clone()));
+        if let Some(rotation) = rotation {
+            destination.set_file_rotation(rotation);
+        }
+        #[cfg(feature = "compression")]
+        if let Some(compress) = self.compress.or_else(|| previous.map(LogDestination::file_compression)) {
+            destination.set_file_compression(compress);
+        }
+        match self.console {
+            Console::Stdout => destination.log_to_stdout(),
+            Console::Stderr => destination.log_to_stderr(),
+            Console::Split => destination.log_to_split_console(),
+            Console::Off => {}
+        }
+        destination.set_stdout_level(self.console_level);
+        destination.set_file_format(match self.file_format {
+            FileFormat::Text => LogFormat::Text,
+            FileFormat::Json => LogFormat::Json,
+        });
+        for file in &self.files {
+            destination.add_file(file.path.clone(), file.level);
+        }
+        if let Some(template) = &self.template {
+            destination.set_template(template.clone());
+        }
+        destination.set_timestamp_format(self.timestamp.clone());
+        destination.set_timestamp_utc(self.utc);
+        destination
+    }
+}
+
+/// Describes the changes of the settings of a logger, given its effective levels.
+fn describe(
+    changes: &mut Vec<String>,
+    logger: &str,
+    old: &LoggerSettings,
+    old_level: LogLevel,
+    new: &LoggerSettings,
+    new_level: LogLevel,
+) {
+    if old_level != new_level {
+        changes.push(format!("{}: level {} -> {}", logger, old_level, new_level));
+    }
+    if old.paranoia != new.paranoia {
+        changes.push(format!("{}: paranoia {} -> {}", logger, old.paranoia, new.paranoia));
+    }
+    let destination = |settings: &LoggerSettings| LoggerSettings {
+        level: None,
+        paranoia: false,
+        ..settings.clone()
+    };
+    if destination(old) != destination(new) {
+        changes.push(format!("{}: destinations changed", logger));
+    }
+}
+
+impl<'de> Deserialize<'de> for ModulePath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let module = String::deserialize(deserializer)?;
+        match is_module_path(&module) {
+            true => Ok(ModulePath(module)),
+            false => Err(de::Error::custom(format!(
+                "invalid module path \"{}\", expected identifiers separated by '::'",
+                module
+            ))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Checked {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let settings = LoggerSettings::deserialize(deserializer)?;
+        settings.check().map_err(de::Error::custom)?;
+        Ok(Checked(settings))
+    }
+}
+
+/// Deserializes the settings of the default logger, checked for consistency.
+fn checked<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LoggerSettings, D::Error> {
+    Checked::deserialize(deserializer).map(|Checked(settings)| settings)
+}
+
+/// Deserializes the settings of the module loggers, checked for consistency.
+fn checked_modules<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<ModulePath, LoggerSettings>, D::Error> {
+    let modules = BTreeMap::<ModulePath, Checked>::deserialize(deserializer)?;
+    Ok(modules
+        .into_iter()
+        .map(|(module, Checked(settings))| (module, settings))
+        .collect())
+}
+
+/// The default level of the console and of the files.
+fn trace() -> LogLevel {
+    LogLevel::Trace
+}
+
+/// Deserializes a log level.
+fn required_level<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LogLevel, D::Error> {
+    let level = String::deserialize(deserializer)?;
+    level.parse().map_err(de::Error::custom)
+}
+
+/// Deserializes an optional log level.
+fn level<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<LogLevel>, D::Error> {
+    required_level(deserializer).map(Some)
+}
+
+/// Deserializes and parses a line template.
+fn template<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Template>, D::Error> {
+    let template = String::deserialize(deserializer)?;
+    template.parse().map(Some).map_err(de::Error::custom)
+}
+
+/// Deserializes a timestamp format.
+fn timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TimestampFormat, D::Error> {
+    let format = String::deserialize(deserializer)?;
+    match format.as_str() {
+        "seconds" => Ok(TimestampFormat::Seconds),
+        "millis" => Ok(TimestampFormat::Millis),
+        "micros" => Ok(TimestampFormat::Micros),
+        "nanos" => Ok(TimestampFormat::Nanos),
+        "rfc3339" => Ok(TimestampFormat::Rfc3339),
+        custom if custom.contains('%') => Ok(TimestampFormat::Custom(format)),
+        _ => Err(de::Error::custom(format!(
+            "unknown timestamp format \"{}\", expected one of: seconds, millis, micros, nanos, rfc3339, or a chrono format",
+            format
+        ))),
+    }
+}
+
+/// Deserializes a maximum file size, which cannot be 0.
+fn max_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    match u64::deserialize(deserializer)? {
+        0 => Err(de::Error::custom(
+            "invalid max_size 0, expected a number of bytes greater than 0",
+        )),
+        max_size => Ok(Some(max_size)),
+    }
+}
+
+/// Deserializes a maximum age, e.g. `"30d"`.
+fn max_age<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let age = String::deserialize(deserializer)?;
+    let invalid = || {
+        de::Error::custom(format!(
+            "invalid age \"{}\", expected a number followed by s, m, h, d or w, e.g. \"30d\"",
+            age
+        ))
+    };
+    let (count, unit) = age.split_at(age.char_indices().last().map(|(index, _)| index).unwrap_or(0));
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    count
+        .checked_mul(seconds)
+        .map(|seconds| Some(Duration::from_secs(seconds)))
+        .ok_or_else(invalid)
+}
+
+/// Returns the dotted path of the key at `offset`: the header of the enclosing table followed by the
+/// key of the line, if any.
+fn key_at(source: &str, offset: usize) -> Option<String> {
+    let line_start = source[..offset].rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let line = source[line_start..].lines().next().unwrap_or("").trim();
+    if line.starts_with('[') {
+        return Some(line.trim_matches(|c| c == '[' || c == ']').trim().to_string());
+    }
+
+    let key = line
+        .split_once('=')
+        .map(|(key, _)| key.trim())
+        .filter(|key| !key.is_empty());
+    let table = source[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with('['))
+        .map(|header| header.trim_matches(|c| c == '[' || c == ']').trim());
+    match (table, key) {
+        (Some(table), Some(key)) => Some(format!("{}.{}", table, key)),
+        (None, Some(key)) => Some(key.to_string()),
+        (Some(table), None) => Some(table.to_string()),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Logger;
+    use crate::loggers::global_logger::DefaultLoggerTrait;
+
+    #[test]
+    fn rotation_keys() {
+        let config: LogConfig = r#"
+[default]
+rotation = "size"
+max_size = 1048576
+keep = 3
+
+[modules."my_crate::db"]
+rotation = "daily"
+max_age = "30d"
+
+[modules."my_crate::net"]
+level = "info"
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            config.default_settings().rotation(),
+            Some(RotationPolicy::Size {
+                max_bytes: 1048576,
+                keep: 3
+            })
+        );
+        assert_eq!(
+            config.modules()["my_crate::db"].rotation(),
+            Some(RotationPolicy::Time {
+                period: RotationPeriod::Daily,
+                pattern: None,
+                max_age: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            })
+        );
+        assert_eq!(config.modules()["my_crate::net"].rotation(), None);
+
+        let error = "[default]\nmax_age = \"2w\"\n".parse::<LogConfig>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 1, key default: max_age requires rotation = \"hourly\", \"daily\" or \"weekly\""
+        );
+
+        let error = "[default]\nrotation = \"size\"\nmax_size = 0\n"
+            .parse::<LogConfig>()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 12, key default.max_size: invalid max_size 0, expected a number of bytes greater than 0"
+        );
+
+        for age in ["30µ", "µ", "", "d", "-1d", "30"] {
+            let source = format!("[default]\nrotation = \"daily\"\nmax_age = \"{}\"\n", age);
+            let error = source.parse::<LogConfig>().unwrap_err();
+            assert!(error.to_string().contains("invalid age"), "{}: {}", age, error);
+        }
+    }
+
+    #[test]
+    fn apply_keeps_rotation_set_in_code() {
+        let size = RotationPolicy::Size {
+            max_bytes: 1024,
+            keep: 2,
+        };
+        Logger::set_file_rotation(size.clone());
+
+        let config: LogConfig = "[default]\nlevel = \"info\"\n".parse().unwrap();
+        config.apply();
+        assert_eq!(DefaultLogger::log_destination().file_rotation(), &size);
+
+        let config: LogConfig = "[default]\nrotation = \"never\"\n".parse().unwrap();
+        config.apply();
+        assert_eq!(DefaultLogger::log_destination().file_rotation(), &RotationPolicy::Never);
+    }
+}
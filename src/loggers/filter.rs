@@ -0,0 +1,239 @@
+//! This module parses `RUST_LOG`-style filter directives into default and module log levels.
+//!
+//! A filter is a comma-separated list of directives:
+//!
+//! - `level`: sets the log level of the `DEFAULT_LOGGER`, e.g. `warn`.
+//! - `module=level`: sets the log level of a module in `MODULES_LOGGER`, e.g. `my_crate::db=trace`.
+//! - `module`: sets the log level of a module to `trace`, e.g. `my_crate`. A bare directive is read
+//!   as a level if it is one, so `info` always sets the default level.
+//!
+//! Levels are case-insensitive, whitespace around directives is ignored, and empty directives are
+//! skipped. Module settings only change the level: the paranoia flag and the log destinations of an
+//! already configured module are kept.
+//!
+//! # Usage
+//!
+//! ```
+//! use log_x::loggers::filter::Directives;
+//! use log_x::loggers::log_levels::LogLevel;
+//!
+//! let directives: Directives = "warn,my_crate::db=trace,my_crate::http=info".parse().unwrap();
+//! assert_eq!(directives.default_level(), Some(LogLevel::Warn));
+//! assert_eq!(directives.modules()[0], ("my_crate::db".to_string(), LogLevel::Trace));
+//!
+//! // a bare module path enables all the records of the module
+//! let directives: Directives = "my_crate".parse().unwrap();
+//! assert_eq!(directives.modules(), [("my_crate".to_string(), LogLevel::Trace)]);
+//!
+//! // apply the directives to DEFAULT_LOGGER and MODULES_LOGGER
+//! directives.apply();
+//!
+//! // malformed directives and unknown levels are reported
+//! assert!("warn,my_crate::db=verbose".parse::<Directives>().is_err());
+//! assert!("my crate=info".parse::<Directives>().is_err());
+//! ```
+//!
+//! The same syntax can be read from an environment variable with
+//! [`Logger::init_from_env`](crate::Logger::init_from_env).
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use super::global_logger::DefaultLogger;
+use super::log_levels::LogLevel;
+use super::mod_logger::ModLogger;
+
+/// A parsed list of filter directives.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Directives {
+    default_level: Option<LogLevel>,
+    modules: Vec<(String, LogLevel)>,
+}
+
+/// The error returned when a filter directive cannot be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterError {
+    /// The level of a directive is not a known log level.
+    UnknownLevel {
+        /// The 1-based position of the directive in the filter.
+        position: usize,
+        /// The directive containing the unknown level.
+        directive: String,
+        /// The unknown level.
+        level: String,
+    },
+    /// A directive does not follow the `level`, `module=level` or `module` syntax.
+    MalformedDirective {
+        /// The 1-based position of the directive in the filter.
+        position: usize,
+        /// The malformed directive.
+        directive: String,
+        /// Why the directive is malformed.
+        reason: &'static str,
+    },
+    /// The environment variable holding the filter is not valid unicode.
+    NotUnicode {
+        /// The name of the environment variable.
+        var: String,
+    },
+}
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::UnknownLevel {
+                position,
+                directive,
+                level,
+            } => write!(
+                f,
+                "directive {} \"{}\": unknown log level \"{}\", expected one of: off, error, warn, info, debug, trace",
+                position, directive, level
+            ),
+            FilterError::MalformedDirective {
+                position,
+                directive,
+                reason,
+            } => write!(f, "directive {} \"{}\": {}", position, directive, reason),
+            FilterError::NotUnicode { var } => write!(f, "environment variable {} is not valid unicode", var),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl Directives {
+    /// Parses a comma-separated list of filter directives.
+    pub fn parse(filter: &str) -> Result<Self, FilterError> {
+        let mut directives = Directives::default();
+
+        for (index, directive) in filter.split(',').enumerate() {
+            let directive = directive.trim();
+            if directive.is_empty() {
+                continue;
+            }
+
+            let position = index + 1;
+            let malformed = |reason| FilterError::MalformedDirective {
+                position,
+                directive: directive.to_string(),
+                reason,
+            };
+            let parse_level = |level: &str| {
+                level.parse::<LogLevel>().map_err(|_| FilterError::UnknownLevel {
+                    position,
+                    directive: directive.to_string(),
+                    level: level.trim().to_string(),
+                })
+            };
+
+            match directive.split_once('=') {
+                None => match parse_level(directive) {
+                    Ok(level) => directives.default_level = Some(level),
+                    Err(_) if is_module_path(directive) => {
+                        directives.modules.push((directive.to_string(), LogLevel::Trace));
+                    }
+                    Err(_) => return Err(malformed("expected a log level or a module path")),
+                },
+                Some((module, level)) => {
+                    let module = module.trim();
+                    if module.is_empty() {
+                        return Err(malformed("missing module path before '='"));
+                    }
+                    if level.trim().is_empty() {
+                        return Err(malformed("missing log level after '='"));
+                    }
+                    if level.contains('=') {
+                        return Err(malformed("expected a single '=' between module path and log level"));
+                    }
+                    if !is_module_path(module) {
+                        return Err(malformed("invalid module path, expected identifiers separated by '::'"));
+                    }
+                    directives.modules.push((module.to_string(), parse_level(level)?));
+                }
+            }
+        }
+
+        Ok(directives)
+    }
+
+    /// Returns the log level for the `DEFAULT_LOGGER`, if the filter sets one.
+    pub fn default_level(&self) -> Option<LogLevel> {
+        self.default_level
+    }
+
+    /// Returns the module log levels, in the order they appear in the filter.
+    pub fn modules(&self) -> &[(String, LogLevel)] {
+        &self.modules
+    }
+
+    /// Applies the directives to `DEFAULT_LOGGER` and `MODULES_LOGGER`.
+    pub fn apply(&self) {
+        if let Some(level) = self.default_level {
+            DefaultLogger::set_log_level(level);
+        }
+        for (module, level) in &self.modules {
+            ModLogger::update_mod_log_level(module, *level);
+        }
+    }
+}
+
+impl FromStr for Directives {
+    type Err = FilterError;
+
+    fn from_str(filter: &str) -> Result<Self, Self::Err> {
+        Directives::parse(filter)
+    }
+}
+
+/// Checks that `module` is a list of identifiers separated by `::`.
+pub(crate) fn is_module_path(module: &str) -> bool {
+    module.split("::").all(|segment| {
+        let mut chars = segment.chars();
+        matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_directives() {
+        let directives = Directives::parse("warn, my_crate::db=TRACE,,my_crate::http=info").unwrap();
+        assert_eq!(directives.default_level(), Some(LogLevel::Warn));
+        assert_eq!(
+            directives.modules(),
+            [
+                ("my_crate::db".to_string(), LogLevel::Trace),
+                ("my_crate::http".to_string(), LogLevel::Info)
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_bare_module() {
+        let directives = Directives::parse("my_crate").unwrap();
+        assert_eq!(directives.default_level(), None);
+        assert_eq!(directives.modules(), [("my_crate".to_string(), LogLevel::Trace)]);
+
+        let directives = Directives::parse("info,my_crate::db").unwrap();
+        assert_eq!(directives.default_level(), Some(LogLevel::Info));
+        assert_eq!(directives.modules(), [("my_crate::db".to_string(), LogLevel::Trace)]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Directives::parse("my-crate").unwrap_err().to_string(),
+            "directive 1 \"my-crate\": expected a log level or a module path"
+        );
+        assert_eq!(
+            Directives::parse("warn,my_crate=verbose").unwrap_err().to_string(),
+            "directive 2 \"my_crate=verbose\": unknown log level \"verbose\", expected one of: off, error, warn, info, debug, trace"
+        );
+        assert!(Directives::parse("my crate=info").is_err());
+        assert!(Directives::parse("=info").is_err());
+        assert!(Directives::parse("my_crate=").is_err());
+    }
+}
@@ -36,7 +36,13 @@ use std::fmt::Debug;
 use std::sync::{LazyLock, RwLock};
 
 use super::log_levels::LogLevel;
+use super::snapshot;
+use crate::output::format::LogFormat;
 use crate::output::logdest::LogDestination;
+use crate::output::rotation::RotationPolicy;
+use crate::output::sink::Sink;
+use crate::output::template::Template;
+use crate::output::timestamp::TimestampFormat;
 
 // Define global static variables for common log levels
 pub static DEFAULT_LOGGER: LazyLock<RwLock<DefaultLogger>> = LazyLock::new(|| RwLock::new(DefaultLogger::default()));
@@ -58,18 +64,73 @@ pub trait DefaultLoggerTrait {
     fn get_paranoia() -> bool {
         DefaultLogger::paranoia()
     }
-    /// Log to file
+    /// Log to file. Records are written to disk right away, unless buffering is enabled with
+    /// `Logger::set_file_buffering`: buffered records are then lost at exit unless `Logger::flush` is
+    /// called or the guard of `Logger::init` is dropped first.
     fn log_to_file(file: impl Into<String>) {
         DefaultLogger::log_to_file(file);
     }
+    /// Add a file to log to, with the most verbose level written to it
+    fn add_log_file(file: impl Into<String>, level: LogLevel) {
+        DefaultLogger::add_log_file(file, level);
+    }
+    /// Remove the file of the given path
+    fn remove_log_file(file: &str) {
+        DefaultLogger::remove_log_file(file);
+    }
+    /// Set the format of the lines written to the log file
+    fn set_file_format(format: LogFormat) {
+        DefaultLogger::set_file_format(format);
+    }
+    /// Set the rotation policy of the log file
+    fn set_file_rotation(rotation: RotationPolicy) {
+        DefaultLogger::set_file_rotation(rotation);
+    }
+    /// Compress the rotated log files with gzip
+    #[cfg(feature = "compression")]
+    fn set_file_compression(compression: bool) {
+        DefaultLogger::set_file_compression(compression);
+    }
     /// Log to stdout
     fn log_to_stdout() {
         DefaultLogger::log_to_stdout();
     }
+    /// Log to stderr
+    fn log_to_stderr() {
+        DefaultLogger::log_to_stderr();
+    }
+    /// Log Warn and Error to stderr, the other levels to stdout
+    fn log_to_split_console() {
+        DefaultLogger::log_to_split_console();
+    }
     /// Remove file logging
     fn remove_file() {
         DefaultLogger::remove_file();
     }
+    /// Set the most verbose level printed to stdout
+    fn set_stdout_level(level: LogLevel) {
+        DefaultLogger::set_stdout_level(level);
+    }
+    /// Set the most verbose level written to a custom sink
+    fn set_sink_level(name: &str, level: LogLevel) {
+        DefaultLogger::set_sink_level(name, level);
+    }
+    /// Set the template of the console and text file lines
+    fn set_template(template: Template) {
+        DefaultLogger::set_template(template);
+    }
+    /// Restore the built-in layout of the console and text file lines
+    fn remove_template() {
+        DefaultLogger::remove_template();
+    }
+    /// Set the format of the timestamps
+    fn set_timestamp_format(format: TimestampFormat) {
+        DefaultLogger::set_timestamp_format(format);
+    }
+    /// Write the timestamps in UTC rather than in local time
+    fn set_timestamp_utc(utc: bool) {
+        DefaultLogger::set_timestamp_utc(utc);
+    }
     /// Remove stdout logging
     fn remove_stdout() {
         DefaultLogger::remove_stdout();
@@ -78,6 +139,14 @@ pub trait DefaultLoggerTrait {
     fn silent() {
         DefaultLogger::silent();
     }
+    /// Attach a custom sink, replacing the sink of the same name
+    fn add_sink(name: &str, sink: impl Sink + 'static) {
+        DefaultLogger::add_sink(name, sink);
+    }
+    /// Detach a custom sink
+    fn remove_sink(name: &str) {
+        DefaultLogger::remove_sink(name);
+    }
     /// get log destination
     fn log_destination() -> LogDestination {
         DefaultLogger::log_destination()
@@ -117,6 +186,7 @@ impl DefaultLogger {
                 eprintln!("Failed to set the default log level variable in DEFAULT_LOGGER: {e}");
             }
         }
+        snapshot::invalidate();
     }
 
     /// Sets the global paranoia setting.
@@ -129,6 +199,7 @@ impl DefaultLogger {
                 eprintln!("Failed to set the paranoia variable in DEFAULT_LOGGER: {e}");
             }
         }
+        snapshot::invalidate();
     }
 
     /// Gets the current global log level.
@@ -153,7 +224,9 @@ impl DefaultLogger {
         }
     }
 
-    // Log to file
+    /// Log to file. Records are written to disk right away, unless buffering is enabled with
+    /// `Logger::set_file_buffering`: buffered records are then lost at exit unless `Logger::flush` is
+    /// called or the guard of `Logger::init` is dropped first.
     pub fn log_to_file(file: impl Into<String>) {
         match DEFAULT_LOGGER.write() {
             Ok(mut default_logger) => {
@@ -163,6 +236,73 @@ impl DefaultLogger {
                 eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
             }
         }
+        snapshot::invalidate();
+    }
+
+    // Add a file to log to, with the most verbose level written to it
+    pub fn add_log_file(file: impl Into<String>, level: LogLevel) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.add_file(file, level);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Remove the file of the given path
+    pub fn remove_log_file(file: &str) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.remove_file_at(file);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the format of the lines written to the log file
+    pub fn set_file_format(format: LogFormat) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.set_file_format(format);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the rotation policy of the log file
+    pub fn set_file_rotation(rotation: RotationPolicy) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.set_file_rotation(rotation);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Compress the rotated log files with gzip
+    #[cfg(feature = "compression")]
+    pub fn set_file_compression(compression: bool) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.set_file_compression(compression);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
     }
 
     // Log to stdout
@@ -175,6 +315,33 @@ impl DefaultLogger {
                 eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
             }
         }
+        snapshot::invalidate();
+    }
+
+    // Log to stderr
+    pub fn log_to_stderr() {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.log_to_stderr();
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Log Warn and Error to stderr, the other levels to stdout
+    pub fn log_to_split_console() {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.log_to_split_console();
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
     }
 
     // Remove file logging
@@ -187,6 +354,85 @@ impl DefaultLogger {
                 eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
             }
         }
+        snapshot::invalidate();
+    }
+
+    // Set the most verbose level printed to stdout
+    pub fn set_stdout_level(level: LogLevel) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.set_stdout_level(level);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the most verbose level written to a custom sink
+    pub fn set_sink_level(name: &str, level: LogLevel) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.set_sink_level(name, level);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the template of the console and text file lines
+    pub fn set_template(template: Template) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.set_template(template);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Restore the built-in layout of the console and text file lines
+    pub fn remove_template() {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.remove_template();
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the format of the timestamps
+    pub fn set_timestamp_format(format: TimestampFormat) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.set_timestamp_format(format);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Write the timestamps in UTC rather than in local time
+    pub fn set_timestamp_utc(utc: bool) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.set_timestamp_utc(utc);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
     }
 
     // Remove stdout logging
@@ -199,6 +445,7 @@ impl DefaultLogger {
                 eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
             }
         }
+        snapshot::invalidate();
     }
 
     // Silence logging
@@ -211,6 +458,33 @@ impl DefaultLogger {
                 eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
             }
         }
+        snapshot::invalidate();
+    }
+
+    // Attach a custom sink, replacing the sink of the same name
+    pub fn add_sink(name: &str, sink: impl Sink + 'static) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.add_sink(name, sink);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Detach a custom sink
+    pub fn remove_sink(name: &str) {
+        match DEFAULT_LOGGER.write() {
+            Ok(mut default_logger) => {
+                default_logger.log_destination.remove_sink(name);
+            }
+            Err(e) => {
+                eprintln!("Failed to set the default log destination variable in DEFAULT_LOGGER: {e}");
+            }
+        }
+        snapshot::invalidate();
     }
 
     /// get log destination
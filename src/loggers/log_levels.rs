@@ -20,12 +20,18 @@
 //! as strings with associated colors. The colors are defined using the `Color` enum from the
 //! `crate::terminal::colors` module.
 //!
-//! Additionally, the `LogLevel` enum provides a `from_str` method to create a `LogLevel` value
+//! Additionally, the `LogLevel` enum implements `From<&str>` to create a `LogLevel` value
 //! from a string representation. If the string does not match any known log level, `LogLevel::Off`
-//! is returned.
+//! is returned. Use [`str::parse`] (`FromStr`) instead to get a [`ParseLevelError`] for unknown levels.
+//!
+//! [`STATIC_MAX_LEVEL`] is the most verbose level the log macros are compiled for, set with the
+//! `max_level_*` cargo features, overridden by the `release_max_level_*` ones in the builds without
+//! debug assertions. The macros of more verbose levels expand to code that type-checks their
+//! arguments and is never run, whatever the runtime configuration.
 use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
 
-use crate::terminal::colors::Color;
+use crate::terminal::colors::{Color, Colorize};
 
 // Define an enum to represent log levels
 #[derive(Clone, Debug, PartialEq, PartialOrd, Copy, Default)]
@@ -40,6 +46,64 @@ pub enum LogLevel {
     Trace,
 }
 
+/// The most verbose level the log macros are compiled for, `Trace` without any `max_level_*` or
+/// `release_max_level_*` feature.
+///
+/// In the builds without debug assertions, a `release_max_level_*` feature takes precedence over the
+/// `max_level_*` ones, whether it is more or less verbose: `max_level_debug` and
+/// `release_max_level_trace` compile the macros for `Debug` in debug builds and for `Trace` in
+/// release builds. In the builds with debug assertions, the `release_max_level_*` features are
+/// ignored. When several features of the same kind are enabled, the least verbose level wins.
+pub const STATIC_MAX_LEVEL: LogLevel =
+    static_max_level(cfg!(debug_assertions), MAX_LEVEL_FEATURE, RELEASE_MAX_LEVEL_FEATURE);
+
+/// The least verbose level of the `max_level_*` features enabled, if any.
+const MAX_LEVEL_FEATURE: Option<LogLevel> = if cfg!(feature = "max_level_off") {
+    Some(LogLevel::Off)
+} else if cfg!(feature = "max_level_error") {
+    Some(LogLevel::Error)
+} else if cfg!(feature = "max_level_warn") {
+    Some(LogLevel::Warn)
+} else if cfg!(feature = "max_level_info") {
+    Some(LogLevel::Info)
+} else if cfg!(feature = "max_level_debug") {
+    Some(LogLevel::Debug)
+} else if cfg!(feature = "max_level_trace") {
+    Some(LogLevel::Trace)
+} else {
+    None
+};
+
+/// The least verbose level of the `release_max_level_*` features enabled, if any.
+const RELEASE_MAX_LEVEL_FEATURE: Option<LogLevel> = if cfg!(feature = "release_max_level_off") {
+    Some(LogLevel::Off)
+} else if cfg!(feature = "release_max_level_error") {
+    Some(LogLevel::Error)
+} else if cfg!(feature = "release_max_level_warn") {
+    Some(LogLevel::Warn)
+} else if cfg!(feature = "release_max_level_info") {
+    Some(LogLevel::Info)
+} else if cfg!(feature = "release_max_level_debug") {
+    Some(LogLevel::Debug)
+} else if cfg!(feature = "release_max_level_trace") {
+    Some(LogLevel::Trace)
+} else {
+    None
+};
+
+/// Computes [`STATIC_MAX_LEVEL`] from the levels of the enabled features.
+const fn static_max_level(
+    debug_assertions: bool,
+    max_level: Option<LogLevel>,
+    release_max_level: Option<LogLevel>,
+) -> LogLevel {
+    match (debug_assertions, release_max_level, max_level) {
+        (false, Some(level), _) => level,
+        (_, _, Some(level)) => level,
+        _ => LogLevel::Trace,
+    }
+}
+
 // Implement the Display trait for LogLevel
 impl Display for LogLevel {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -59,7 +123,6 @@ impl Display for LogLevel {
 /// Creates a `LogLevel` value from a string representation.
 /// If the string does not match any known log level, `LogLevel::Off` is returned.
 /// This function is used to parse log levels from configuration files or command-line arguments.
-
 impl From<&str> for LogLevel {
     fn from(level: &str) -> Self {
         match level.to_uppercase().as_str() {
@@ -74,15 +137,54 @@ impl From<&str> for LogLevel {
     }
 }
 
+impl FromStr for LogLevel {
+    type Err = ParseLevelError;
+
+    /// Parses a log level, ignoring case and surrounding whitespace.
+    /// Unlike `From<&str>`, unknown levels are reported as an error.
+    fn from_str(level: &str) -> Result<Self, Self::Err> {
+        match level.trim().to_uppercase().as_str() {
+            "TRACE" => Ok(LogLevel::Trace),
+            "DEBUG" => Ok(LogLevel::Debug),
+            "INFO" => Ok(LogLevel::Info),
+            "WARN" => Ok(LogLevel::Warn),
+            "ERROR" => Ok(LogLevel::Error),
+            "OFF" => Ok(LogLevel::Off),
+            _ => Err(ParseLevelError {
+                level: level.to_string(),
+            }),
+        }
+    }
+}
+
+/// The error returned when parsing an unknown log level with [`str::parse`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseLevelError {
+    /// The string that could not be parsed.
+    pub level: String,
+}
+
+impl Display for ParseLevelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown log level \"{}\", expected one of: off, error, warn, info, debug, trace",
+            self.level
+        )
+    }
+}
+
+impl std::error::Error for ParseLevelError {}
+
 impl From<String> for LogLevel {
     fn from(level: String) -> Self {
         level.as_str().into() // Delegate to the &str implementation
     }
 }
 
-impl Into<&'static str> for LogLevel {
-    fn into(self) -> &'static str {
-        match self {
+impl From<LogLevel> for &'static str {
+    fn from(level: LogLevel) -> Self {
+        match level {
             LogLevel::Trace => "TRACE",
             LogLevel::Debug => "DEBUG",
             LogLevel::Info => "INFO",
@@ -93,25 +195,56 @@ impl Into<&'static str> for LogLevel {
     }
 }
 
-impl Into<String> for LogLevel {
-    fn into(self) -> String {
-        self.to_string()
+impl From<LogLevel> for String {
+    fn from(level: LogLevel) -> Self {
+        level.to_string()
     }
 }
 
 /// LogLevel implementation
 impl LogLevel {
     /// Formats a `LogLevel` value as a string with associated colors. The colors are defined using the `Color` enum.
-    /// Returns a colorized string representation of the log level.
+    /// Returns a colorized string representation of the log level, plain if colors are disabled.
     pub fn colorized(&self) -> String {
-        let (color, level_str) = match self {
-            LogLevel::Trace => (Color::Cyan, "TRACE"),
-            LogLevel::Debug => (Color::Blue, "DEBUG"),
-            LogLevel::Info => (Color::Green, "INFO"),
-            LogLevel::Warn => (Color::Yellow, "WARN"),
-            LogLevel::Error => (Color::Red, "ERROR"),
-            LogLevel::Off => (Color::White, "OFF"),
-        };
-        format!("{}{}{}", color.to_ansi_code(), level_str, Color::Reset.to_ansi_code())
+        self.to_string().colorize(self.color())
+    }
+
+    /// Returns the color of the log level on the console.
+    pub fn color(&self) -> Color {
+        match self {
+            LogLevel::Trace => Color::Cyan,
+            LogLevel::Debug => Color::Blue,
+            LogLevel::Info => Color::Green,
+            LogLevel::Warn => Color::Yellow,
+            LogLevel::Error => Color::Red,
+            LogLevel::Off => Color::White,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_max_level_precedence() {
+        use LogLevel::*;
+
+        // Without features, every macro is compiled
+        assert_eq!(static_max_level(true, None, None), Trace);
+        assert_eq!(static_max_level(false, None, None), Trace);
+
+        // `max_level_*` applies to every build
+        assert_eq!(static_max_level(true, Some(Info), None), Info);
+        assert_eq!(static_max_level(false, Some(Info), None), Info);
+
+        // `release_max_level_*` only applies without debug assertions
+        assert_eq!(static_max_level(true, None, Some(Warn)), Trace);
+        assert_eq!(static_max_level(false, None, Some(Warn)), Warn);
+
+        // and then overrides `max_level_*`, whether it is more or less verbose
+        assert_eq!(static_max_level(true, Some(Debug), Some(Trace)), Debug);
+        assert_eq!(static_max_level(false, Some(Debug), Some(Trace)), Trace);
+        assert_eq!(static_max_level(false, Some(Debug), Some(Error)), Error);
     }
 }
@@ -25,13 +25,40 @@
 //! - `get_mod_paranoia(module: &str) -> bool`: Retrieves the paranoia flag for a specific module from the
 //!   `MODULES_LOGGER` map.
 //!
+//! # Hierarchical matching
+//!
+//! Module paths are matched on their `::`-separated segments: a setting on `my_crate::net` also applies
+//! to `my_crate::net::tcp` and any other descendant, unless a more specific entry exists for it.
+//! `my_crate::network` is not a descendant of `my_crate::net`. The log calls resolve their module
+//! in the snapshot of the configuration, see [`snapshot`](crate::loggers::snapshot).
+//!
+//! ```
+//! use log_x::Logger;
+//! use log_x::loggers::log_levels::LogLevel;
+//! use log_x::loggers::mod_logger::ModuleLoggerTrait;
+//!
+//! Logger::set_mod_logging("my_crate::net", LogLevel::Debug, false);
+//! assert_eq!(Logger::get_mod_log_level("my_crate::net::tcp"), Some(LogLevel::Debug));
+//! assert_eq!(Logger::get_mod_log_level("my_crate::network"), None);
+//!
+//! Logger::set_mod_logging("my_crate::net::tcp", LogLevel::Trace, false);
+//! assert_eq!(Logger::get_mod_log_level("my_crate::net::tcp::listener"), Some(LogLevel::Trace));
+//! assert_eq!(Logger::get_mod_log_level("my_crate::net::udp"), Some(LogLevel::Debug));
+//! ```
+//!
 //! Error handling is performed using `eprintln!` to print error messages if the read or write lock on
 //! `MODULES_LOGGER` fails.
 use std::collections::HashMap;
 use std::sync::{LazyLock, RwLock};
 
 use super::log_levels::LogLevel;
+use super::snapshot;
+use crate::output::format::LogFormat;
 use crate::output::logdest::LogDestination;
+use crate::output::rotation::RotationPolicy;
+use crate::output::sink::Sink;
+use crate::output::template::Template;
+use crate::output::timestamp::TimestampFormat;
 
 // Define a global static variable for module-specific log levels
 /// A global static variable that holds module-specific log levels and paranoia settings.
@@ -47,7 +74,8 @@ pub trait ModuleLoggerTrait {
     fn get_mod_name(module: &str) -> String {
         ModLogger::get_mod_name(module)
     }
-    /// Retrieves the log level for a specific module. Returns `None` if the module is not found.
+    /// Retrieves the log level for a specific module, inherited from its closest configured parent module
+    /// if the module itself is not configured. Returns `None` if no configuration applies.
     fn get_mod_log_level(module: &str) -> Option<LogLevel> {
         ModLogger::get_mod_log_level(module)
     }
@@ -55,21 +83,89 @@ pub trait ModuleLoggerTrait {
     fn get_mod_paranoia(module: &str) -> bool {
         ModLogger::get_mod_paranoia(module)
     }
-    /// Log to file
+    /// Log to file. Records are written to disk right away, unless buffering is enabled with
+    /// `Logger::set_file_buffering`: buffered records are then lost at exit unless `Logger::flush` is
+    /// called or the guard of `Logger::init` is dropped first.
     fn set_mod_log_to_file(module: &str, file: impl Into<String>) {
         ModLogger::set_mod_log_to_file(module, file.into());
     }
 
+    /// Add a file to log to, with the most verbose level written to it
+    fn add_mod_log_file(module: &str, file: impl Into<String>, level: LogLevel) {
+        ModLogger::add_mod_log_file(module, file, level);
+    }
+
+    /// Remove the file of the given path
+    fn remove_mod_log_file(module: &str, file: &str) {
+        ModLogger::remove_mod_log_file(module, file);
+    }
+
+    /// Set the format of the lines written to the log file
+    fn set_mod_file_format(module: &str, format: LogFormat) {
+        ModLogger::set_mod_file_format(module, format);
+    }
+
+    /// Set the rotation policy of the log file
+    fn set_mod_file_rotation(module: &str, rotation: RotationPolicy) {
+        ModLogger::set_mod_file_rotation(module, rotation);
+    }
+
+    /// Compress the rotated log files with gzip
+    #[cfg(feature = "compression")]
+    fn set_mod_file_compression(module: &str, compression: bool) {
+        ModLogger::set_mod_file_compression(module, compression);
+    }
+
     /// Log to stdout
     fn set_mod_log_to_stdout(module: &str) {
         ModLogger::set_mod_log_to_stdout(module);
     }
 
+    /// Log to stderr
+    fn set_mod_log_to_stderr(module: &str) {
+        ModLogger::set_mod_log_to_stderr(module);
+    }
+
+    /// Log Warn and Error to stderr, the other levels to stdout
+    fn set_mod_log_to_split_console(module: &str) {
+        ModLogger::set_mod_log_to_split_console(module);
+    }
+
     /// Remove file logging
     fn remove_mod_log_to_file(module: &str) {
         ModLogger::remove_mod_log_to_file(module);
     }
 
+    /// Set the most verbose level printed to stdout
+    fn set_mod_stdout_level(module: &str, level: LogLevel) {
+        ModLogger::set_mod_stdout_level(module, level);
+    }
+
+    /// Set the most verbose level written to a custom sink
+    fn set_mod_sink_level(module: &str, name: &str, level: LogLevel) {
+        ModLogger::set_mod_sink_level(module, name, level);
+    }
+
+    /// Set the template of the console and text file lines
+    fn set_mod_template(module: &str, template: Template) {
+        ModLogger::set_mod_template(module, template);
+    }
+
+    /// Restore the built-in layout of the console and text file lines
+    fn remove_mod_template(module: &str) {
+        ModLogger::remove_mod_template(module);
+    }
+
+    /// Set the format of the timestamps
+    fn set_mod_timestamp_format(module: &str, format: TimestampFormat) {
+        ModLogger::set_mod_timestamp_format(module, format);
+    }
+
+    /// Write the timestamps in UTC rather than in local time
+    fn set_mod_timestamp_utc(module: &str, utc: bool) {
+        ModLogger::set_mod_timestamp_utc(module, utc);
+    }
+
     /// Remove stdout logging
     fn remove_mod_log_stdout(module: &str) {
         ModLogger::remove_mod_log_stdout(module);
@@ -80,6 +176,16 @@ pub trait ModuleLoggerTrait {
         ModLogger::set_mod_logging_silent(module);
     }
 
+    /// Attach a custom sink, replacing the sink of the same name
+    fn add_mod_sink(module: &str, name: &str, sink: impl Sink + 'static) {
+        ModLogger::add_mod_sink(module, name, sink);
+    }
+
+    /// Detach a custom sink
+    fn remove_mod_sink(module: &str, name: &str) {
+        ModLogger::remove_mod_sink(module, name);
+    }
+
     /// get log destination
     fn get_mod_log_destination(module: &str) -> Option<LogDestination> {
         ModLogger::get_mod_log_destination(module)
@@ -104,13 +210,11 @@ pub struct ModLogger {
 }
 
 impl ModLogger {
-    /// Get the logging configuration for a module if exists
+    /// Get the logging configuration that applies to a module if exists, either its own or the one
+    /// of its closest configured parent module.
     pub fn get(module: &str) -> Option<ModLogger> {
         match MODULES_LOGGER.read() {
-            Ok(modules_log_level) => match modules_log_level.get(module) {
-                Some(mod_logger) => Some(mod_logger.clone()),
-                None => None,
-            },
+            Ok(modules_log_level) => lookup(&modules_log_level, module).cloned(),
             Err(e) => {
                 eprintln!(
                     "Failed to get the log level for module {} in MODULES_LOGGER: {:?}",
@@ -142,12 +246,40 @@ impl ModLogger {
                 );
             }
         }
+        snapshot::invalidate();
+    }
+
+    /// Sets the log level for a specific module, keeping its paranoia flag and log destinations
+    /// if the module is already configured.
+    pub fn update_mod_log_level(module: &str, log_level: LogLevel) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => match modules_log_level.get_mut(module) {
+                Some(mod_logger) => mod_logger.log_level = log_level,
+                None => {
+                    modules_log_level.insert(
+                        module.to_string(),
+                        ModLogger {
+                            module: module.to_string(),
+                            log_level,
+                            ..Default::default()
+                        },
+                    );
+                }
+            },
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log level for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
     }
 
     /// Retrieves the name of the module.
     pub fn get_mod_name(module: &str) -> String {
         match MODULES_LOGGER.read() {
-            Ok(modules_log_level) => match modules_log_level.get(module) {
+            Ok(modules_log_level) => match lookup(&modules_log_level, module) {
                 Some(mod_logger) => mod_logger.module.clone(),
                 None => "".to_string(),
             },
@@ -164,10 +296,7 @@ impl ModLogger {
     /// Retrieves the log level for a specific module. Returns `None` if the module is not found.
     pub fn get_mod_log_level(module: &str) -> Option<LogLevel> {
         match MODULES_LOGGER.read() {
-            Ok(modules_log_level) => match modules_log_level.get(module) {
-                Some(mod_logger) => Some(mod_logger.log_level.clone()),
-                None => None,
-            },
+            Ok(modules_log_level) => lookup(&modules_log_level, module).map(|mod_logger| mod_logger.log_level),
             Err(e) => {
                 eprintln!(
                     "Failed to get the log level for module {} in MODULES_LOGGER: {:?}",
@@ -181,7 +310,7 @@ impl ModLogger {
     /// Retrieves the paranoia flag for a specific module.
     pub fn get_mod_paranoia(module: &str) -> bool {
         match MODULES_LOGGER.read() {
-            Ok(modules_log_level) => match modules_log_level.get(module) {
+            Ok(modules_log_level) => match lookup(&modules_log_level, module) {
                 Some(mod_logger) => mod_logger.paranoia,
                 None => false,
             },
@@ -195,7 +324,9 @@ impl ModLogger {
         }
     }
 
-    // Log to file
+    /// Log to file. Records are written to disk right away, unless buffering is enabled with
+    /// `Logger::set_file_buffering`: buffered records are then lost at exit unless `Logger::flush` is
+    /// called or the guard of `Logger::init` is dropped first.
     pub fn set_mod_log_to_file(module: &str, file: impl Into<String>) {
         match MODULES_LOGGER.write() {
             Ok(mut modules_log_level) => {
@@ -210,6 +341,98 @@ impl ModLogger {
                 );
             }
         }
+        snapshot::invalidate();
+    }
+
+    // Add a file to log to, with the most verbose level written to it
+    pub fn add_mod_log_file(module: &str, file: impl Into<String>, level: LogLevel) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.add_file(file, level);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Remove the file of the given path
+    pub fn remove_mod_log_file(module: &str, file: &str) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.remove_file_at(file);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the format of the lines written to the log file
+    pub fn set_mod_file_format(module: &str, format: LogFormat) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.set_file_format(format);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the rotation policy of the log file
+    pub fn set_mod_file_rotation(module: &str, rotation: RotationPolicy) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.set_file_rotation(rotation);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Compress the rotated log files with gzip
+    #[cfg(feature = "compression")]
+    pub fn set_mod_file_compression(module: &str, compression: bool) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.set_file_compression(compression);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
     }
 
     // Log to stdout
@@ -227,6 +450,43 @@ impl ModLogger {
                 );
             }
         }
+        snapshot::invalidate();
+    }
+
+    // Log to stderr
+    pub fn set_mod_log_to_stderr(module: &str) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.log_to_stderr();
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Log Warn and Error to stderr, the other levels to stdout
+    pub fn set_mod_log_to_split_console(module: &str) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.log_to_split_console();
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
     }
 
     // Remove file logging
@@ -244,6 +504,115 @@ impl ModLogger {
                 );
             }
         }
+        snapshot::invalidate();
+    }
+
+    // Set the most verbose level printed to stdout
+    pub fn set_mod_stdout_level(module: &str, level: LogLevel) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.set_stdout_level(level);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the most verbose level written to a custom sink
+    pub fn set_mod_sink_level(module: &str, name: &str, level: LogLevel) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.set_sink_level(name, level);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the template of the console and text file lines
+    pub fn set_mod_template(module: &str, template: Template) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.set_template(template);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Restore the built-in layout of the console and text file lines
+    pub fn remove_mod_template(module: &str) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.remove_template();
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Set the format of the timestamps
+    pub fn set_mod_timestamp_format(module: &str, format: TimestampFormat) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.set_timestamp_format(format);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Write the timestamps in UTC rather than in local time
+    pub fn set_mod_timestamp_utc(module: &str, utc: bool) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.set_timestamp_utc(utc);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
     }
 
     // Remove stdout logging
@@ -261,6 +630,7 @@ impl ModLogger {
                 );
             }
         }
+        snapshot::invalidate();
     }
 
     // Silence logging
@@ -278,15 +648,65 @@ impl ModLogger {
                 );
             }
         }
+        snapshot::invalidate();
+    }
+
+    // Attach a custom sink, replacing the sink of the same name
+    pub fn add_mod_sink(module: &str, name: &str, sink: impl Sink + 'static) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.add_sink(name, sink);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    // Detach a custom sink
+    pub fn remove_mod_sink(module: &str, name: &str) {
+        match MODULES_LOGGER.write() {
+            Ok(mut modules_log_level) => {
+                if let Some(mod_logger) = modules_log_level.get_mut(module) {
+                    mod_logger.log_destinations.remove_sink(name);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to set the log destination for module {} in MODULES_LOGGER: {:?}",
+                    module, e
+                );
+            }
+        }
+        snapshot::invalidate();
+    }
+
+    /// Get the log destinations of all the configured modules
+    pub fn all_log_destinations() -> Vec<LogDestination> {
+        match MODULES_LOGGER.read() {
+            Ok(modules_log_level) => modules_log_level
+                .values()
+                .map(|mod_logger| mod_logger.log_destinations.clone())
+                .collect(),
+            Err(e) => {
+                eprintln!("Failed to read the log destinations in MODULES_LOGGER: {:?}", e);
+                Vec::new()
+            }
+        }
     }
 
     /// get log destination
     pub fn get_mod_log_destination(module: &str) -> Option<LogDestination> {
         match MODULES_LOGGER.read() {
-            Ok(modules_log_level) => match modules_log_level.get(module) {
-                Some(mod_logger) => Some(mod_logger.log_destinations.clone()),
-                None => None,
-            },
+            Ok(modules_log_level) => {
+                lookup(&modules_log_level, module).map(|mod_logger| mod_logger.log_destinations.clone())
+            }
             Err(e) => {
                 eprintln!(
                     "Failed to get the log destination for module {} in MODULES_LOGGER: {:?}",
@@ -300,7 +720,7 @@ impl ModLogger {
     /// debug DEFAULT_LOGGER
     pub fn debug_mod_logger(module: &str) -> String {
         match MODULES_LOGGER.read() {
-            Ok(modules_log_level) => match modules_log_level.get(module) {
+            Ok(modules_log_level) => match lookup(&modules_log_level, module) {
                 Some(mod_logger) => format!("{:?}", mod_logger),
                 None => "".to_string(),
             },
@@ -314,3 +734,20 @@ impl ModLogger {
         }
     }
 }
+
+/// Returns the `MODULES_LOGGER` entry that applies to `module`: its own entry or the entry of its
+/// closest configured parent module.
+fn lookup<'a>(modules: &'a HashMap<String, ModLogger>, module: &str) -> Option<&'a ModLogger> {
+    resolve(modules, module).and_then(|key| modules.get(key))
+}
+
+/// Walks up the `::`-separated segments of `module` and returns the longest configured module path.
+pub(crate) fn resolve<'a, V>(modules: &HashMap<String, V>, module: &'a str) -> Option<&'a str> {
+    let mut candidate = module;
+    loop {
+        if modules.contains_key(candidate) {
+            return Some(candidate);
+        }
+        candidate = &candidate[..candidate.rfind("::")?];
+    }
+}
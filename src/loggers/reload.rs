@@ -0,0 +1,149 @@
+//! This module reloads the logging configuration file when it changes.
+//!
+//! A [`ConfigWatcher`], started with [`Logger::watch_config`](crate::Logger::watch_config), applies
+//! the configuration file, then polls its modification time and size on a background thread. When
+//! they change, the file is parsed again and, if valid, applied to `DEFAULT_LOGGER` and
+//! `MODULES_LOGGER` at once, see [`LogConfig::apply`]. Each change is printed to stderr, e.g.
+//! `Reloaded logging.toml: module my_crate::db: level info -> trace`.
+//!
+//! A file that cannot be read or parsed is reported to stderr and the previous configuration is
+//! kept, until the file changes again. The reload events are printed to stderr like the other
+//! internal diagnostics, rather than logged, so the configuration they report on can never filter
+//! them out. Polling works on every platform, without file system notifications. The watcher stops
+//! when dropped.
+//!
+//! This module is only available with the `config` cargo feature enabled.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use log_x::Logger;
+//!
+//! let _watcher = Logger::watch_config("logging.toml", Duration::from_secs(2)).expect("invalid logging.toml");
+//!
+//! // edit logging.toml to turn a module up to `trace` without restarting
+//! ```
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use super::config::{ConfigError, LogConfig};
+
+/// Reloads the configuration file when it changes, until dropped.
+#[must_use = "the configuration file is no longer watched once the watcher is dropped"]
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    /// Set to stop the polling thread, which waits on the condition variable between two polls.
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// The modification time and size of the watched file, `None` if it cannot be read.
+type Version = Option<(SystemTime, u64)>;
+
+impl ConfigWatcher {
+    /// Applies the configuration file at `path`, then starts polling it every `interval`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, without changing the configuration or starting to watch, if the file cannot
+    /// be read or is invalid.
+    pub fn start(path: impl AsRef<Path>, interval: Duration) -> Result<Self, ConfigError> {
+        let path = path.as_ref().to_path_buf();
+        let version = version(&path);
+        let config = LogConfig::load(&path)?;
+        config.apply();
+
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let worker = {
+            let path = path.clone();
+            let stop = stop.clone();
+            thread::Builder::new()
+                .name("log_x-config".to_string())
+                .spawn(move || watch(&path, interval, config, version, &stop))
+        };
+        let worker = match worker {
+            Ok(worker) => Some(worker),
+            Err(e) => {
+                eprintln!(
+                    "Failed to start the configuration watcher thread, {} is not reloaded: {e}",
+                    path.display()
+                );
+                None
+            }
+        };
+
+        Ok(ConfigWatcher { path, stop, worker })
+    }
+
+    /// Returns the path of the watched file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stops watching the file, keeping the current configuration.
+    pub fn stop(self) {}
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        let (stopped, wake) = &*self.stop;
+        *stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        wake.notify_all();
+        if let Some(worker) = self.worker.take()
+            && worker.join().is_err()
+        {
+            eprintln!("The configuration watcher thread panicked");
+        }
+    }
+}
+
+/// Polls the file every `interval` and applies its new versions, until stopped.
+fn watch(path: &Path, interval: Duration, mut config: LogConfig, mut last: Version, stop: &(Mutex<bool>, Condvar)) {
+    let (stopped, wake) = stop;
+    loop {
+        let guard = stopped.lock().unwrap_or_else(PoisonError::into_inner);
+        let (guard, _) = wake
+            .wait_timeout_while(guard, interval, |stopped| !*stopped)
+            .unwrap_or_else(PoisonError::into_inner);
+        if *guard {
+            return;
+        }
+        drop(guard);
+
+        let current = version(path);
+        if current == last {
+            continue;
+        }
+        last = current;
+
+        match LogConfig::load(path) {
+            Ok(new) => {
+                let changes = config.changes(&new);
+                if changes.is_empty() {
+                    continue;
+                }
+                new.apply();
+                config = new;
+                for change in changes {
+                    eprintln!("Reloaded {}: {}", path.display(), change);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Failed to reload {}: {}, keeping the previous configuration",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Returns the modification time and size of the file.
+fn version(path: &Path) -> Version {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
@@ -0,0 +1,310 @@
+//! This module keeps a lock-free view of the logging configuration for the log calls.
+//!
+//! The configuration is written through `DEFAULT_LOGGER` and `MODULES_LOGGER`, whose setters call
+//! [`invalidate`] once the change is made. The log calls never take these locks on their fast path:
+//!
+//! - A record more verbose than [`max_level`], the most verbose level any logger writes, is
+//!   rejected with a single atomic load.
+//! - The other records resolve the logger they are logged from, exactly once, in an immutable
+//!   snapshot of the configuration. Each thread keeps the last snapshot it used, and only rebuilds
+//!   it after the [`generation`] of the configuration changed.
+//! - Each thread also remembers the logger each module resolved to in its snapshot, so a module
+//!   path is only walked segment by segment once per thread and configuration change.
+//!
+//! ```
+//! use log_x::Logger;
+//! use log_x::loggers::global_logger::DefaultLoggerTrait;
+//! use log_x::loggers::log_levels::LogLevel;
+//! use log_x::loggers::snapshot;
+//!
+//! Logger::set_log_level(LogLevel::Info);
+//! assert!(Logger::resolve(LogLevel::Debug, "my_crate").is_none());
+//! assert_eq!(snapshot::max_level(), LogLevel::Info);
+//!
+//! let config = Logger::resolve(LogLevel::Warn, "my_crate").unwrap();
+//! assert!(!config.from_module());
+//! ```
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::loggers::global_logger::DEFAULT_LOGGER;
+use crate::loggers::log_levels::LogLevel;
+use crate::loggers::mod_logger::{MODULES_LOGGER, resolve};
+use crate::output::logdest::LogDestination;
+
+/// The generation of the configuration, bumped by every change.
+static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/// The most verbose level written by any logger, `Trace` until the snapshot is rebuilt after a change.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Trace as u8);
+
+/// The last snapshot built, shared by the threads.
+static SNAPSHOT: RwLock<Option<Arc<Snapshot>>> = RwLock::new(None);
+
+/// The most module paths each thread remembers the logger of, beyond which they are resolved on
+/// every call.
+const MAX_RESOLVED: usize = 1024;
+
+thread_local! {
+    /// The last snapshot used by the thread.
+    static CACHED: RefCell<Option<Cached>> = const { RefCell::new(None) };
+}
+
+/// The configuration of the logger a record is logged from: the default logger, or the module
+/// logger that applies to its module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoggerConfig {
+    level: LogLevel,
+    max_level: LogLevel,
+    paranoia: bool,
+    from_module: bool,
+    destination: Arc<LogDestination>,
+}
+
+impl LoggerConfig {
+    fn new(level: LogLevel, paranoia: bool, from_module: bool, destination: LogDestination) -> Self {
+        let max_level = match destination.max_level() < level {
+            true => destination.max_level(),
+            false => level,
+        };
+        LoggerConfig {
+            level,
+            max_level,
+            paranoia,
+            from_module,
+            destination: Arc::new(destination),
+        }
+    }
+
+    /// Returns the log level of the logger.
+    pub fn level(&self) -> LogLevel {
+        self.level
+    }
+
+    /// Returns whether a record of the given level is written by the logger: its level enables it and
+    /// at least one of its destinations accepts it.
+    pub fn enabled(&self, level: LogLevel) -> bool {
+        level <= self.max_level
+    }
+
+    /// Returns the paranoia setting of the logger.
+    pub fn paranoia(&self) -> bool {
+        self.paranoia
+    }
+
+    /// Returns whether the logger is a module logger.
+    pub fn from_module(&self) -> bool {
+        self.from_module
+    }
+
+    /// Returns the destinations of the logger.
+    pub fn destination(&self) -> &Arc<LogDestination> {
+        &self.destination
+    }
+}
+
+/// An immutable copy of the configuration of the default and module loggers.
+#[derive(Debug)]
+struct Snapshot {
+    generation: u64,
+    default: LoggerConfig,
+    modules: HashMap<String, LoggerConfig>,
+}
+
+impl Snapshot {
+    /// Copies the configuration, holding the locks of `DEFAULT_LOGGER` and `MODULES_LOGGER` in turn.
+    fn build(generation: u64) -> Self {
+        let default = match DEFAULT_LOGGER.read() {
+            Ok(default_logger) => LoggerConfig::new(
+                default_logger.default_logger,
+                default_logger.paranoia,
+                false,
+                default_logger.log_destination.clone(),
+            ),
+            Err(e) => {
+                eprintln!("Failed to read the default logger in DEFAULT_LOGGER: {e}");
+                LoggerConfig::new(LogLevel::Off, false, false, LogDestination::default())
+            }
+        };
+        let modules = match MODULES_LOGGER.read() {
+            Ok(modules_log_level) => modules_log_level
+                .iter()
+                .map(|(module, mod_logger)| {
+                    let config = LoggerConfig::new(
+                        mod_logger.log_level,
+                        mod_logger.paranoia,
+                        true,
+                        mod_logger.log_destinations.clone(),
+                    );
+                    (module.clone(), config)
+                })
+                .collect(),
+            Err(e) => {
+                eprintln!("Failed to read the module loggers in MODULES_LOGGER: {:?}", e);
+                HashMap::new()
+            }
+        };
+        Snapshot {
+            generation,
+            default,
+            modules,
+        }
+    }
+
+    /// Returns the most verbose level written by any logger.
+    fn max_level(&self) -> LogLevel {
+        self.modules
+            .values()
+            .map(|config| config.max_level)
+            .fold(self.default.max_level, |max, level| match level > max {
+                true => level,
+                false => max,
+            })
+    }
+
+    /// Returns the configuration of the logger a module logs with.
+    fn resolve(&self, module: &str) -> &LoggerConfig {
+        match resolve(&self.modules, module) {
+            Some(key) => &self.modules[key],
+            None => &self.default,
+        }
+    }
+}
+
+/// A snapshot used by a thread, with the loggers its modules resolved to.
+struct Cached {
+    snapshot: Arc<Snapshot>,
+    /// The configuration of the logger each module logs with, at most `MAX_RESOLVED` modules.
+    resolved: HashMap<String, LoggerConfig>,
+}
+
+impl Cached {
+    fn new(snapshot: Arc<Snapshot>) -> Self {
+        Cached {
+            snapshot,
+            resolved: HashMap::new(),
+        }
+    }
+
+    /// Returns the configuration of the logger a record of the given level logged from `module` is
+    /// written by, resolving the module in the snapshot on its first record only.
+    fn resolve_config(&mut self, level: LogLevel, module: &str) -> Option<LoggerConfig> {
+        if let Some(config) = self.resolved.get(module) {
+            return config.enabled(level).then(|| config.clone());
+        }
+
+        let config = self.snapshot.resolve(module);
+        let enabled = config.enabled(level).then(|| config.clone());
+        if self.resolved.len() < MAX_RESOLVED {
+            self.resolved.insert(module.to_string(), config.clone());
+        }
+        enabled
+    }
+}
+
+/// Returns the most verbose level written by any logger. Records more verbose than this level are
+/// disabled whatever module they are logged from.
+pub fn max_level() -> LogLevel {
+    match MAX_LEVEL.load(Ordering::Acquire) {
+        0 => LogLevel::Off,
+        1 => LogLevel::Error,
+        2 => LogLevel::Warn,
+        3 => LogLevel::Info,
+        4 => LogLevel::Debug,
+        _ => LogLevel::Trace,
+    }
+}
+
+/// Returns the generation of the configuration, bumped by every change of the default or module
+/// loggers.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+/// Marks the configuration as changed. Called by the setters of `DEFAULT_LOGGER` and
+/// `MODULES_LOGGER` after the change, without holding their locks.
+pub(crate) fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+    // Every record is let through until the exact maximum level is known again
+    MAX_LEVEL.store(LogLevel::Trace as u8, Ordering::Release);
+    #[cfg(feature = "log")]
+    crate::integrations::log_bridge::sync_max_level(LogLevel::Trace);
+}
+
+/// Returns the configuration of the logger a record of the given level logged from `module` is
+/// written by, `None` if the record is disabled. Rebuilds the snapshot of the thread after a change.
+pub(crate) fn resolve_config(level: LogLevel, module: &str) -> Option<LoggerConfig> {
+    if level > max_level() {
+        return None;
+    }
+
+    let generation = generation();
+    let config = CACHED.try_with(|cached| {
+        let mut cached = cached.borrow_mut();
+        let cached = match cached.take() {
+            Some(previous) if previous.snapshot.generation == generation => cached.insert(previous),
+            _ => cached.insert(Cached::new(current(generation))),
+        };
+        cached.resolve_config(level, module)
+    });
+    // The thread local storage is gone while the thread exits
+    config.unwrap_or_else(|_| {
+        let snapshot = current(generation);
+        let config = snapshot.resolve(module);
+        config.enabled(level).then(|| config.clone())
+    })
+}
+
+/// Returns the shared snapshot of the given generation, building it if needed.
+fn current(generation: u64) -> Arc<Snapshot> {
+    if let Ok(shared) = SNAPSHOT.read()
+        && let Some(snapshot) = shared.as_ref()
+        && snapshot.generation == generation
+    {
+        return snapshot.clone();
+    }
+
+    let snapshot = Arc::new(Snapshot::build(generation));
+    let max_level = snapshot.max_level();
+    MAX_LEVEL.store(max_level as u8, Ordering::Release);
+    #[cfg(feature = "log")]
+    crate::integrations::log_bridge::sync_max_level(max_level);
+    // A change made while building keeps every record let through until the next rebuild
+    if GENERATION.load(Ordering::Acquire) != generation {
+        MAX_LEVEL.store(LogLevel::Trace as u8, Ordering::Release);
+        #[cfg(feature = "log")]
+        crate::integrations::log_bridge::sync_max_level(LogLevel::Trace);
+    }
+
+    if let Ok(mut shared) = SNAPSHOT.write()
+        && shared.as_ref().is_none_or(|shared| shared.generation < generation)
+    {
+        *shared = Some(snapshot.clone());
+    }
+    snapshot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Logger;
+    use crate::loggers::mod_logger::ModuleLoggerTrait;
+
+    #[test]
+    fn resolved_modules_follow_the_configuration() {
+        let parent = "log_x_snapshot_tests";
+        let module = "log_x_snapshot_tests::db::pool";
+        Logger::set_mod_logging(parent, LogLevel::Warn, false);
+        assert!(Logger::resolve(LogLevel::Info, module).is_none());
+        assert!(Logger::resolve(LogLevel::Warn, module).unwrap().from_module());
+
+        Logger::set_mod_logging(parent, LogLevel::Info, false);
+        assert!(Logger::resolve(LogLevel::Info, module).is_some());
+
+        Logger::set_mod_logging("log_x_snapshot_tests::db", LogLevel::Error, false);
+        assert!(Logger::resolve(LogLevel::Warn, module).is_none());
+        assert!(Logger::resolve(LogLevel::Info, parent).is_some());
+    }
+}
@@ -1,2 +1,3 @@
 #[macro_use]
+#[allow(clippy::module_inception)]
 pub mod macros;
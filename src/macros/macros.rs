@@ -9,6 +9,20 @@
 //! - `log_trace!`: Logs a trace message.
 //! - `timestamp!`: Generates a formatted timestamp string representing the current time.
 //!
+//! ## Structured fields
+//!
+//! Every log macro accepts `key = value` fields before the message. Values are converted with
+//! `FieldValue::from`, `%value` captures a value with `Display` and `?value` with `Debug`.
+//! Fields are printed as `key=value` after the message.
+//!
+//! ```
+//! use log_x::{log_info, log_warn};
+//!
+//! log_info!(user_id = 42, request_id = "abc", "login ok");
+//! log_warn!(path = %std::path::Path::new("/tmp").display(), retry = ?Some(2), "slow disk {}", "sda");
+//! log_info!(cache_hit = true);
+//! ```
+//!
 //! ## `log_error!` Macro
 //!
 //! Logs an error message with optional format arguments.
@@ -103,157 +117,112 @@
 //! which would cause the duration calculation to fail.
 //!
 //!
+// Shared implementation of the log macros.
+// Munches the leading `key = value` fields one at a time, then formats the remaining tokens as the message.
+#[doc(hidden)]
 #[macro_export]
-macro_rules! log_error {
-    // Print empty message for error log
-    () => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Error,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                "".to_string()
-            )
-        );
+macro_rules! __log_x_log {
+    // Field captured through its Display implementation
+    (@fields $level:expr, [$($fields:tt)*] $key:ident = %$value:expr $(, $($rest:tt)*)?) => {
+        $crate::__log_x_log!(
+            @fields $level,
+            [$($fields)* ($crate::fields::Field::new(stringify!($key), $crate::fields::FieldValue::display(&$value)))]
+            $($($rest)*)?
+        )
     };
 
-    // Pattern for error log message with format arguments
-    ($($arg:tt)*) => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Error,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                format!($($arg)*)
-            )
-        );
+    // Field captured through its Debug implementation
+    (@fields $level:expr, [$($fields:tt)*] $key:ident = ?$value:expr $(, $($rest:tt)*)?) => {
+        $crate::__log_x_log!(
+            @fields $level,
+            [$($fields)* ($crate::fields::Field::new(stringify!($key), $crate::fields::FieldValue::debug(&$value)))]
+            $($($rest)*)?
+        )
     };
+
+    // Field converted with FieldValue::from
+    (@fields $level:expr, [$($fields:tt)*] $key:ident = $value:expr $(, $($rest:tt)*)?) => {
+        $crate::__log_x_log!(
+            @fields $level,
+            [$($fields)* ($crate::fields::Field::new(stringify!($key), $crate::fields::FieldValue::from($value)))]
+            $($($rest)*)?
+        )
+    };
+
+    // Print empty message
+    (@fields $level:expr, [$($fields:tt)*]) => {
+        $crate::__log_x_log!(@emit $level, [$($fields)*] String::new())
+    };
+
+    // Pattern for log message with format arguments
+    (@fields $level:expr, [$($fields:tt)*] $($arg:tt)+) => {
+        $crate::__log_x_log!(@emit $level, [$($fields)*] format!($($arg)+))
+    };
+
+    // Levels above the static maximum level are compiled out. The callsite caches whether it is
+    // enabled, the logger is resolved once, and the timestamp, the message and the fields are only
+    // built for the enabled records
+    (@emit $level:expr, [$($fields:tt)*] $message:expr) => {{
+        if ($level as u8) <= ($crate::loggers::log_levels::STATIC_MAX_LEVEL as u8) {
+            static CALLSITE: $crate::loggers::callsite::Callsite =
+                $crate::loggers::callsite::Callsite::new($level, module_path!(), file!(), line!());
+            if CALLSITE.is_enabled() {
+                if let Some(config) = $crate::Logger::resolve(CALLSITE.level(), CALLSITE.module()) {
+                    $crate::Logger::log_with(
+                        $crate::LogMetadata::new(
+                            $crate::output::timestamp::Timestamp::now(),
+                            CALLSITE.level(),
+                            CALLSITE.file(),
+                            CALLSITE.module(),
+                            CALLSITE.line(),
+                            $message
+                        )
+                        .with_fields(vec![$($fields),*]),
+                        config,
+                    );
+                }
+            }
+        }
+    }};
 }
+
 #[macro_export]
-macro_rules! log_warn {
-    // Print empty message for warning log
-    () => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Warn,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                "".to_string()
-            )
-        );
+macro_rules! log_error {
+    // Optional `key = value` fields followed by an optional message with format arguments
+    ($($arg:tt)*) => {
+        $crate::__log_x_log!(@fields $crate::loggers::log_levels::LogLevel::Error, [] $($arg)*)
     };
+}
 
-    // Pattern for warning log message with format arguments
+#[macro_export]
+macro_rules! log_warn {
+    // Optional `key = value` fields followed by an optional message with format arguments
     ($($arg:tt)*) => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Warn,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                format!($($arg)*)
-            )
-        );
+        $crate::__log_x_log!(@fields $crate::loggers::log_levels::LogLevel::Warn, [] $($arg)*)
     };
 }
 
 #[macro_export]
 macro_rules! log_info {
-    // Print empty message for info log
-    () => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Info,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                "".to_string()
-            )
-        );
-    };
-
-    // Pattern for info log message with format arguments
+    // Optional `key = value` fields followed by an optional message with format arguments
     ($($arg:tt)*) => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Info,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                format!($($arg)*)
-            )
-        );
+        $crate::__log_x_log!(@fields $crate::loggers::log_levels::LogLevel::Info, [] $($arg)*)
     };
 }
 
 #[macro_export]
 macro_rules! log_debug {
-    // Print empty message for debug log
-    () => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Debug,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                "".to_string()
-            )
-        );
-    };
-
-    // Pattern for debug log message with format arguments
+    // Optional `key = value` fields followed by an optional message with format arguments
     ($($arg:tt)*) => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Debug,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                format!($($arg)*)
-            )
-        );
+        $crate::__log_x_log!(@fields $crate::loggers::log_levels::LogLevel::Debug, [] $($arg)*)
     };
 }
 
 #[macro_export]
 macro_rules! log_trace {
-    // Print empty message for trace log
-    () => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Trace,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                "".to_string()
-            )
-        );
-    };
-
-    // Pattern for trace log message with format arguments
+    // Optional `key = value` fields followed by an optional message with format arguments
     ($($arg:tt)*) => {
-        log_x::Logger::log(
-            &mut log_x::LogMetadata::new(
-                timestamp!(),
-                log_x::loggers::log_levels::LogLevel::Trace,
-                file!(),
-                module_path!().to_string(),
-                line!(),
-                format!($($arg)*)
-            )
-        );
+        $crate::__log_x_log!(@fields $crate::loggers::log_levels::LogLevel::Trace, [] $($arg)*)
     };
 }
 
@@ -305,20 +274,31 @@ macro_rules! log_trace {
 #[macro_export]
 macro_rules! timestamp {
     // default: seconds precision (local time)
-    () => {{ ::chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string() }};
+    () => {{ $crate::timestamp!(@format Seconds) }};
 
     // ---- specific shorthands FIRST ----
-    (milliseconds) => {{ ::chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string() }};
+    (milliseconds) => {{ $crate::timestamp!(@format Millis) }};
     (millis) => {{ $crate::timestamp!(milliseconds) }};
 
-    (micro) => {{ ::chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.6f").to_string() }};
+    (micro) => {{ $crate::timestamp!(@format Micros) }};
     (micros) => {{ $crate::timestamp!(micro) }};
 
-    (nano) => {{ ::chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.9f").to_string() }};
+    (nano) => {{ $crate::timestamp!(@format Nanos) }};
     (nanos) => {{ $crate::timestamp!(nano) }};
 
     // custom format string — keep LAST, limit to literals so identifiers don’t get captured
-    ($fmt:literal) => {{ ::chrono::Local::now().format($fmt).to_string() }};
+    ($fmt:literal) => {{
+        $crate::output::timestamp::Timestamp::now().format(
+            &$crate::output::timestamp::TimestampFormat::Custom($fmt.to_string()),
+            false,
+        )
+    }};
+
+    // the formats shared with the destinations, see `output::timestamp::TimestampFormat`
+    (@format $format:ident) => {{
+        $crate::output::timestamp::Timestamp::now()
+            .format(&$crate::output::timestamp::TimestampFormat::$format, false)
+    }};
 
     // helpful error for anything else
     ($unknown:tt) => {
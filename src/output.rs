@@ -1 +1,11 @@
+pub mod async_writer;
+#[cfg(feature = "compression")]
+pub mod compression;
+pub mod error;
+pub mod file_writer;
+pub mod format;
 pub mod logdest;
+pub mod rotation;
+pub mod sink;
+pub mod template;
+pub mod timestamp;
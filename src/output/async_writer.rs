@@ -0,0 +1,320 @@
+//! This module writes the log records on a dedicated background thread.
+//!
+//! By default records are written on the thread calling the log macros. Once the asynchronous mode
+//! is started with [`Logger::start_async`](crate::Logger::start_async), `Logger::log` only checks
+//! the log level and pushes the record on a bounded queue, drained by a writer thread which formats
+//! it and writes it to its destinations.
+//!
+//! When the queue is full, the [`OverflowPolicy`] decides whether the caller waits for room, or
+//! whether the new record or the oldest queued record is dropped. Dropped records are counted, the
+//! count is printed to stderr and returned by [`Logger::shutdown`](crate::Logger::shutdown), which
+//! writes the queued records and stops the writer thread. Records logged after the shutdown are
+//! written synchronously again. The shutdown also happens when the guard returned by `start_async`
+//! is dropped, see [`guard`](crate::guard).
+//!
+//! [`Logger::flush`](crate::Logger::flush) waits for the queued records to be written before
+//! flushing the destinations. A destination panicking while writing a record loses that record
+//! only: the writer thread keeps draining the queue.
+//!
+//! ```
+//! use log_x::Logger;
+//! use log_x::loggers::global_logger::DefaultLoggerTrait;
+//! use log_x::loggers::log_levels::LogLevel;
+//! use log_x::output::async_writer::OverflowPolicy;
+//!
+//! Logger::set_log_level(LogLevel::Info);
+//! let _guard = Logger::start_async(1024, OverflowPolicy::DropOldest);
+//!
+//! log_x::log_info!("written by the writer thread");
+//!
+//! let dropped = Logger::shutdown();
+//! assert_eq!(dropped, 0);
+//! ```
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Condvar, LazyLock, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle, ThreadId};
+
+use crate::LogMetadata;
+use crate::output::logdest::log_to_destination;
+
+/// A global static variable that holds the queue of the asynchronous mode.
+static ASYNC_WRITER: LazyLock<AsyncWriter> = LazyLock::new(AsyncWriter::default);
+
+/// Mirrors the `running` flag of the queue, so that the synchronous mode never takes its lock.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// What to do with a record logged while the queue is full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for the writer thread to make room in the queue.
+    #[default]
+    Block,
+    /// Drop the record being logged.
+    DropNewest,
+    /// Drop the oldest queued record to make room for the record being logged.
+    DropOldest,
+}
+
+/// The queue of records and the writer thread draining it.
+#[derive(Default)]
+struct AsyncWriter {
+    state: Mutex<State>,
+    /// Signaled when a record is queued or the writer thread must stop.
+    queued: Condvar,
+    /// Signaled when the writer thread takes records out of the queue or stops.
+    drained: Condvar,
+    /// Signaled when the writer thread has written all the queued records.
+    idle: Condvar,
+    /// The number of records dropped since the asynchronous mode was started.
+    dropped: AtomicU64,
+    /// The writer thread, `None` when the asynchronous mode is stopped.
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// The state of the queue, protected by the mutex of the `AsyncWriter`.
+#[derive(Default)]
+struct State {
+    records: VecDeque<LogMetadata>,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    /// Whether records are queued, `false` before the start and once the shutdown begins.
+    running: bool,
+    /// The number of records taken out of the queue and not written yet.
+    writing: usize,
+    /// The writer thread, which must never wait on its own queue.
+    worker: Option<ThreadId>,
+}
+
+impl AsyncWriter {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Writes the queued records until the shutdown, then the remaining ones.
+    fn run(&self) {
+        loop {
+            let mut state = self.state();
+            while state.records.is_empty() && state.running {
+                state = self.queued.wait(state).unwrap_or_else(PoisonError::into_inner);
+            }
+            if state.records.is_empty() {
+                self.idle.notify_all();
+                return;
+            }
+
+            let batch: Vec<LogMetadata> = state.records.drain(..).collect();
+            state.writing = batch.len();
+            self.drained.notify_all();
+            drop(state);
+
+            for metadata in &batch {
+                // A panicking sink must not stop the thread, callers would wait for it forever
+                if panic::catch_unwind(AssertUnwindSafe(|| log_to_destination(metadata))).is_err() {
+                    eprintln!("Failed to write a log record: its destination panicked");
+                }
+            }
+
+            let mut state = self.state();
+            state.writing = 0;
+            if state.records.is_empty() {
+                self.idle.notify_all();
+            }
+        }
+    }
+}
+
+/// Starts the writer thread, or updates the capacity and overflow policy of the running one.
+///
+/// # Arguments
+///
+/// * `capacity` - The maximum number of queued records, at least 1.
+/// * `overflow` - What to do with a record logged while the queue is full.
+pub fn start(capacity: usize, overflow: OverflowPolicy) {
+    let writer = &*ASYNC_WRITER;
+    let mut worker = writer.worker.lock().unwrap_or_else(PoisonError::into_inner);
+    {
+        let mut state = writer.state();
+        state.capacity = capacity.max(1);
+        state.overflow = overflow;
+        if worker.is_some() {
+            // Waiting callers may fit in the new capacity
+            writer.drained.notify_all();
+            return;
+        }
+        state.running = true;
+        RUNNING.store(true, Ordering::Release);
+    }
+
+    match thread::Builder::new()
+        .name("log_x-writer".to_string())
+        .spawn(|| ASYNC_WRITER.run())
+    {
+        Ok(handle) => {
+            writer.state().worker = Some(handle.thread().id());
+            *worker = Some(handle);
+        }
+        Err(e) => {
+            writer.state().running = false;
+            RUNNING.store(false, Ordering::Release);
+            eprintln!("Failed to start the log writer thread, logging synchronously: {e}");
+        }
+    }
+}
+
+/// Queues a copy of the record for the writer thread.
+/// Returns `false` if the record must be written synchronously by the caller, when the asynchronous
+/// mode is not running or when called from the writer thread itself.
+pub fn enqueue(metadata: &LogMetadata) -> bool {
+    if !RUNNING.load(Ordering::Acquire) {
+        return false;
+    }
+
+    let writer = &*ASYNC_WRITER;
+    let mut state = writer.state();
+    if !state.running || state.worker == Some(thread::current().id()) {
+        return false;
+    }
+
+    if state.records.len() >= state.capacity {
+        match state.overflow {
+            OverflowPolicy::Block => {
+                while state.records.len() >= state.capacity && state.running {
+                    state = writer.drained.wait(state).unwrap_or_else(PoisonError::into_inner);
+                }
+                if !state.running {
+                    return false;
+                }
+            }
+            OverflowPolicy::DropNewest => {
+                writer.dropped.fetch_add(1, Ordering::Relaxed);
+                return true;
+            }
+            OverflowPolicy::DropOldest => {
+                state.records.pop_front();
+                writer.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    state.records.push_back(metadata.clone());
+    writer.queued.notify_one();
+    true
+}
+
+/// Waits for the writer thread to write the queued records. Does nothing when called from the writer
+/// thread itself.
+pub fn wait_idle() {
+    let writer = &*ASYNC_WRITER;
+    let mut state = writer.state();
+    if state.worker == Some(thread::current().id()) {
+        return;
+    }
+    while state.worker.is_some() && (!state.records.is_empty() || state.writing > 0) {
+        state = writer.idle.wait(state).unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+/// Returns the number of records dropped since the asynchronous mode was started.
+pub fn dropped() -> u64 {
+    ASYNC_WRITER.dropped.load(Ordering::Relaxed)
+}
+
+/// Writes the queued records and stops the writer thread, returning the number of dropped records
+/// and printing it to stderr if any. Does nothing and returns 0 if the asynchronous mode is not running.
+pub fn shutdown() -> u64 {
+    let writer = &*ASYNC_WRITER;
+    let mut worker = writer.worker.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(handle) = worker.take() else {
+        return 0;
+    };
+    if handle.thread().id() == thread::current().id() {
+        // Called from the error handler on the writer thread, which cannot join itself
+        *worker = Some(handle);
+        return 0;
+    }
+
+    {
+        let mut state = writer.state();
+        state.running = false;
+        RUNNING.store(false, Ordering::Release);
+        writer.queued.notify_all();
+        writer.drained.notify_all();
+    }
+    if handle.join().is_err() {
+        eprintln!("The log writer thread panicked, queued records may be lost");
+    }
+    let mut state = writer.state();
+    state.worker = None;
+    // Records left by a panicking writer thread are written by the caller
+    let remaining: Vec<LogMetadata> = state.records.drain(..).collect();
+    drop(state);
+    for metadata in &remaining {
+        log_to_destination(metadata);
+    }
+
+    let dropped = writer.dropped.swap(0, Ordering::Relaxed);
+    if dropped > 0 {
+        eprintln!("{} log records were dropped because the log queue was full", dropped);
+    }
+    dropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Logger;
+    use crate::loggers::log_levels::LogLevel;
+    use crate::loggers::mod_logger::ModuleLoggerTrait;
+    use crate::output::sink::Sink;
+    use crate::output::timestamp::Timestamp;
+    use std::sync::Arc;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    /// Panics on the records whose message is "panic", keeps the messages of the others.
+    #[derive(Default)]
+    struct PanickingSink {
+        written: Mutex<Vec<String>>,
+    }
+
+    impl Sink for PanickingSink {
+        fn log(&self, metadata: &LogMetadata) {
+            if metadata.message() == "panic" {
+                panic!("sink failure");
+            }
+            self.written.lock().unwrap().push(metadata.message().to_string());
+        }
+    }
+
+    #[test]
+    fn panicking_sink_does_not_stop_the_writer_thread() {
+        let module = "log_x_async_writer_tests";
+        let sink = Arc::new(PanickingSink::default());
+        Logger::set_mod_logging(module, LogLevel::Trace, false);
+        Logger::set_mod_logging_silent(module);
+        Logger::add_mod_sink(module, "panicking", sink.clone());
+
+        start(8, OverflowPolicy::Block);
+        for message in ["panic", "written"] {
+            Logger::log(&mut LogMetadata::new(
+                Timestamp::now(),
+                LogLevel::Info,
+                file!(),
+                module,
+                line!(),
+                message,
+            ));
+        }
+
+        let (done, flushed) = mpsc::channel();
+        thread::spawn(move || {
+            Logger::flush();
+            done.send(()).unwrap();
+        });
+        assert!(flushed.recv_timeout(Duration::from_secs(10)).is_ok(), "flush hangs");
+        assert_eq!(*sink.written.lock().unwrap(), ["written"]);
+        shutdown();
+    }
+}
@@ -0,0 +1,348 @@
+//! This module compresses the rotated log files with gzip, on a background thread.
+//!
+//! When compression is enabled on a file destination with a rotation policy, the rotated files are
+//! handed to a single background worker so the logging call path only pays for a rename:
+//!
+//! - With `RotationPolicy::Size`, the log file is renamed to a unique `app.log.pending-<nanos>` file.
+//!   The worker then shifts the archives (`app.log.1.gz` to `app.log.2.gz` and so on), deletes the
+//!   archives beyond `keep`, and compresses the pending file to `app.log.1.gz`. The pending files
+//!   left by a process that exited before they were compressed are archived, as older archives, on
+//!   the first rotation of the file by the next process.
+//! - With `RotationPolicy::Time`, the previous dated file is compressed to `app-2026-10-15.log.gz`
+//!   once the logger switches to the next one.
+//!
+//! A file is compressed to a temporary `.gz.tmp` file which is synced to disk and renamed to its
+//! final `.gz` name before the original file is deleted, so a crash mid-compression never loses the
+//! original file: it is left on disk, uncompressed. If compression fails, the error is routed to the
+//! error handler and the original file is kept.
+//!
+//! [`Logger::shutdown`](crate::Logger::shutdown) waits for the queued compressions to complete.
+//!
+//! This module is only available with the `compression` cargo feature enabled.
+//!
+//! ```
+//! use log_x::Logger;
+//! use log_x::loggers::global_logger::DefaultLoggerTrait;
+//! use log_x::output::rotation::RotationPolicy;
+//!
+//! Logger::log_to_file("app.log");
+//! Logger::set_file_rotation(RotationPolicy::Size {
+//!     max_bytes: 10 * 1024 * 1024,
+//!     keep: 5,
+//! });
+//! // archives are written as app.log.1.gz to app.log.5.gz
+//! Logger::set_file_compression(true);
+//! ```
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Sender, channel};
+use std::sync::{Condvar, LazyLock, Mutex, PoisonError};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+
+use crate::output::error::{LogError, report};
+use crate::output::rotation::{archive_path, remove_if_exists};
+
+/// The sender of the compression jobs, `None` if the worker thread could not be started.
+static COMPRESSION_QUEUE: LazyLock<Option<Sender<Job>>> = LazyLock::new(|| {
+    let (sender, receiver) = channel::<Job>();
+    thread::Builder::new()
+        .name("log_x-compression".to_string())
+        .spawn(move || {
+            for job in receiver {
+                job.run();
+            }
+        })
+        .ok()
+        .map(|_| sender)
+});
+
+/// The number of submitted jobs not completed yet, signaled when it drops to 0.
+static PENDING_JOBS: (Mutex<usize>, Condvar) = (Mutex::new(0), Condvar::new());
+
+/// The log files rotated with compression by this process, whose leftover pending files were recovered.
+static RECOVERED: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// A compression job run by the worker thread.
+enum Job {
+    /// Shift the numbered archives of `base`, then compress `pending` to the first archive.
+    Archive {
+        base: PathBuf,
+        pending: PathBuf,
+        keep: usize,
+    },
+    /// Compress `path` to `path.gz`.
+    Compress { path: PathBuf },
+}
+
+impl Job {
+    fn run(self) {
+        let (path, result) = match self {
+            Job::Archive { base, pending, keep } => {
+                let result = archive(&base, &pending, keep);
+                (base, result)
+            }
+            Job::Compress { path } => {
+                let result = compress_file(&path, &gz_path(&path));
+                (path, result)
+            }
+        };
+
+        if let Err(source) = result {
+            report(LogError::Rotate { path, source });
+        }
+
+        let mut pending = PENDING_JOBS.0.lock().unwrap_or_else(PoisonError::into_inner);
+        *pending -= 1;
+        if *pending == 0 {
+            PENDING_JOBS.1.notify_all();
+        }
+    }
+
+    /// Sends the job to the worker thread, or runs it on the calling thread if there is none.
+    fn submit(self) {
+        *PENDING_JOBS.0.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+        match COMPRESSION_QUEUE.as_ref() {
+            Some(queue) => {
+                if let Err(job) = queue.send(self) {
+                    job.0.run();
+                }
+            }
+            None => self.run(),
+        }
+    }
+}
+
+/// Waits for the queued compressions to complete.
+pub fn wait_idle() {
+    let mut pending = PENDING_JOBS.0.lock().unwrap_or_else(PoisonError::into_inner);
+    while *pending > 0 {
+        pending = PENDING_JOBS.1.wait(pending).unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+/// Returns the path of the compressed file, e.g. `app.log.1.gz`.
+pub fn gz_path(path: &Path) -> PathBuf {
+    let mut gz = OsString::from(path.as_os_str());
+    gz.push(".gz");
+    PathBuf::from(gz)
+}
+
+/// Renames the log file to a unique pending file and queues its compression to the first numbered
+/// archive. The log file must be closed before calling this function.
+pub(crate) fn rotate_by_size_compressed(path: &Path, keep: usize) -> io::Result<()> {
+    // The pending files of the path existing before its first rotation were left by a previous process
+    let first_rotation = RECOVERED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(path.to_path_buf());
+    if first_rotation {
+        for pending in leftover_pending(path)? {
+            match keep {
+                0 => remove_if_exists(&pending)?,
+                _ => Job::Archive {
+                    base: path.to_path_buf(),
+                    pending,
+                    keep,
+                }
+                .submit(),
+            }
+        }
+    }
+
+    if keep == 0 {
+        return remove_if_exists(path);
+    }
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let mut pending = OsString::from(path.as_os_str());
+    pending.push(format!(".pending-{}", nanos));
+    let pending = PathBuf::from(pending);
+
+    fs::rename(path, &pending)?;
+    Job::Archive {
+        base: path.to_path_buf(),
+        pending,
+        keep,
+    }
+    .submit();
+    Ok(())
+}
+
+/// Returns the pending files of the log file, e.g. `app.log.pending-<nanos>`, oldest first.
+fn leftover_pending(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let (Some(directory), Some(name)) = (path.parent(), path.file_name()) else {
+        return Ok(Vec::new());
+    };
+    let directory = match directory.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => directory,
+    };
+    let prefix = format!("{}.pending-", name.to_string_lossy());
+
+    let mut pending = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if let Some(nanos) = name.to_string_lossy().strip_prefix(&prefix)
+            && let Ok(nanos) = nanos.parse::<u128>()
+        {
+            pending.push((nanos, entry.path()));
+        }
+    }
+    pending.sort();
+    Ok(pending.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Queues the compression of a rotated file to `path.gz`.
+pub(crate) fn compress_in_background(path: PathBuf) {
+    Job::Compress { path }.submit();
+}
+
+/// Shifts the numbered archives, compressed or not, and compresses `pending` to `base.1.gz`.
+/// If compression fails, `pending` is kept uncompressed as `base.1`.
+fn archive(base: &Path, pending: &Path, keep: usize) -> io::Result<()> {
+    remove_if_exists(&archive_path(base, keep))?;
+    remove_if_exists(&gz_path(&archive_path(base, keep)))?;
+    for index in (1..keep).rev() {
+        let (from, to) = (archive_path(base, index), archive_path(base, index + 1));
+        rename_if_exists(&from, &to)?;
+        rename_if_exists(&gz_path(&from), &gz_path(&to))?;
+    }
+
+    let first = archive_path(base, 1);
+    match compress_file(pending, &gz_path(&first)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            fs::rename(pending, &first)?;
+            Err(e)
+        }
+    }
+}
+
+/// Compresses `source` to `target` through a temporary file, deleting `source` only once `target`
+/// is on disk.
+fn compress_file(source: &Path, target: &Path) -> io::Result<()> {
+    let mut temporary = OsString::from(target.as_os_str());
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+
+    let compressed = (|| {
+        let mut input = File::open(source)?;
+        let mut encoder = GzEncoder::new(BufWriter::new(File::create(&temporary)?), Compression::default());
+        io::copy(&mut input, &mut encoder)?;
+        let mut output = encoder.finish()?;
+        output.flush()?;
+        output.get_ref().sync_all()?;
+        fs::rename(&temporary, target)
+    })();
+
+    if let Err(e) = compressed {
+        let _ = fs::remove_file(&temporary);
+        return Err(e);
+    }
+    fs::remove_file(source)
+}
+
+/// Renames the file, ignoring a missing source file.
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    /// Creates an empty directory for the test in the temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("log_x-compression-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Returns the decompressed content of the gzip file.
+    fn decompress(path: impl AsRef<Path>) -> String {
+        let mut content = String::new();
+        GzDecoder::new(File::open(path).unwrap())
+            .read_to_string(&mut content)
+            .unwrap();
+        content
+    }
+
+    /// Returns the sorted names of the files of the directory.
+    fn files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn size_archives_decompress_to_original() {
+        let dir = temp_dir("size");
+        let path = dir.join("app.log");
+        for index in 1..=3 {
+            fs::write(&path, format!("record {}\n", index)).unwrap();
+            rotate_by_size_compressed(&path, 2).unwrap();
+        }
+        wait_idle();
+
+        assert_eq!(files(&dir), ["app.log.1.gz", "app.log.2.gz"]);
+        assert_eq!(decompress(dir.join("app.log.1.gz")), "record 3\n");
+        assert_eq!(decompress(dir.join("app.log.2.gz")), "record 2\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dated_file_decompresses_to_original() {
+        let dir = temp_dir("dated");
+        let path = dir.join("app-2026-10-15.log");
+        fs::write(&path, "record 1\nrecord 2\n").unwrap();
+        compress_in_background(path);
+        wait_idle();
+
+        assert_eq!(files(&dir), ["app-2026-10-15.log.gz"]);
+        assert_eq!(decompress(dir.join("app-2026-10-15.log.gz")), "record 1\nrecord 2\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn leftover_pending_files_are_archived() {
+        let dir = temp_dir("leftover");
+        let path = dir.join("app.log");
+        // Left by a previous process exiting before the worker compressed them
+        fs::write(dir.join("app.log.pending-200"), "record 2\n").unwrap();
+        fs::write(dir.join("app.log.pending-100"), "record 1\n").unwrap();
+        fs::write(dir.join("app.log.pending-old"), "not a pending file\n").unwrap();
+        fs::write(&path, "record 3\n").unwrap();
+
+        rotate_by_size_compressed(&path, 5).unwrap();
+        wait_idle();
+
+        assert_eq!(
+            files(&dir),
+            ["app.log.1.gz", "app.log.2.gz", "app.log.3.gz", "app.log.pending-old"]
+        );
+        assert_eq!(decompress(dir.join("app.log.1.gz")), "record 3\n");
+        assert_eq!(decompress(dir.join("app.log.2.gz")), "record 2\n");
+        assert_eq!(decompress(dir.join("app.log.3.gz")), "record 1\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+//! This module defines the errors raised while writing log records and the handler they are routed to.
+//!
+//! Writing a log record never panics: failures to open, write or flush a log file are reported to
+//! the error handler, which prints them to stderr by default. A custom handler can be installed with
+//! [`set_error_handler`], e.g. to count the failures or to forward them to a monitoring system.
+//!
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! use log_x::output::error::set_error_handler;
+//!
+//! static FAILURES: AtomicUsize = AtomicUsize::new(0);
+//!
+//! set_error_handler(|error| {
+//!     FAILURES.fetch_add(1, Ordering::Relaxed);
+//!     eprintln!("logging failed: {}", error);
+//! });
+//! ```
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// A function receiving the errors raised while writing log records.
+pub type ErrorHandler = Arc<dyn Fn(&LogError) + Send + Sync>;
+
+/// The installed error handler, `None` for the default one printing to stderr.
+static ERROR_HANDLER: LazyLock<RwLock<Option<ErrorHandler>>> = LazyLock::new(|| RwLock::new(None));
+
+/// An error raised while writing log records.
+#[derive(Debug)]
+pub enum LogError {
+    /// The log file could not be opened.
+    Open { path: PathBuf, source: io::Error },
+    /// A log record could not be written to the log file.
+    Write { path: PathBuf, source: io::Error },
+    /// The log file could not be flushed.
+    Flush { path: PathBuf, source: io::Error },
+    /// The log file could not be rotated.
+    Rotate { path: PathBuf, source: io::Error },
+}
+
+impl LogError {
+    /// Returns the path of the log file the error relates to.
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            LogError::Open { path, .. }
+            | LogError::Write { path, .. }
+            | LogError::Flush { path, .. }
+            | LogError::Rotate { path, .. } => path,
+        }
+    }
+}
+
+impl Display for LogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::Open { path, source } => write!(f, "Error opening file: {} , {}", path.display(), source),
+            LogError::Write { path, source } => write!(f, "Error writing file: {} , {}", path.display(), source),
+            LogError::Flush { path, source } => write!(f, "Error flushing file: {} , {}", path.display(), source),
+            LogError::Rotate { path, source } => write!(f, "Error rotating file: {} , {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for LogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LogError::Open { source, .. }
+            | LogError::Write { source, .. }
+            | LogError::Flush { source, .. }
+            | LogError::Rotate { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Installs the handler receiving the errors raised while writing log records, replacing the
+/// previous one.
+pub fn set_error_handler(handler: impl Fn(&LogError) + Send + Sync + 'static) {
+    match ERROR_HANDLER.write() {
+        Ok(mut error_handler) => *error_handler = Some(Arc::new(handler)),
+        Err(e) => {
+            eprintln!("Failed to set the error handler: {e}");
+        }
+    }
+}
+
+/// Restores the default error handler, printing the errors to stderr.
+pub fn reset_error_handler() {
+    match ERROR_HANDLER.write() {
+        Ok(mut error_handler) => *error_handler = None,
+        Err(e) => {
+            eprintln!("Failed to reset the error handler: {e}");
+        }
+    }
+}
+
+/// Routes an error to the installed error handler.
+pub fn report(error: LogError) {
+    // Clone the handler so it runs without holding the lock, it may log or reinstall a handler itself
+    let handler = match ERROR_HANDLER.read() {
+        Ok(error_handler) => error_handler.clone(),
+        Err(_) => None,
+    };
+
+    match handler {
+        Some(handler) => handler(&error),
+        None => eprintln!("{}", error),
+    }
+}
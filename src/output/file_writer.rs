@@ -0,0 +1,410 @@
+//! This module keeps the log files open for the lifetime of the application.
+//!
+//! Every log file is opened once, on its first record, and wrapped in a buffered writer stored in the
+//! `FILE_WRITERS` registry, keyed by its absolute path. The default logger and the module loggers
+//! writing to the same path share the same handle, each handle being protected by its own mutex so
+//! records from several threads are never interleaved.
+//!
+//! By default every record is written to the file right away, so nothing is lost when the application
+//! exits, even abruptly. Buffering is opt-in, with [`set_buffered`] or
+//! [`Logger::set_file_buffering`](crate::Logger::set_file_buffering): the buffer is then only written
+//! to the file when it is full, when a `Warn` or `Error` record is written, and on
+//! [`Logger::flush`](crate::Logger::flush).
+//!
+//! **Warning**: with buffering enabled, the records still buffered when the process exits are lost.
+//! Keep the guard returned by [`Logger::init`](crate::Logger::init) alive until the end of `main`, or
+//! call [`Logger::flush`](crate::Logger::flush) before exiting. `std::process::exit` and a panic
+//! without unwinding skip both.
+//!
+//! Errors are never raised to the logging call site, they are routed to the error handler of the
+//! [`error`](crate::output::error) module.
+//!
+//! The handle also applies the [`RotationPolicy`] of the destination before each write, see the
+//! [`rotation`](crate::output::rotation) module, handing the rotated files to the background worker
+//! of the [`compression`](crate::output::compression) module when compression is enabled. A path has
+//! a single policy: if several loggers write to the same path with different policies, the first
+//! policy applied since the last configuration change is kept and the conflict is reported to the
+//! error handler. Other processes writing to the same file are not coordinated with.
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+
+use crate::loggers::snapshot;
+#[cfg(feature = "compression")]
+use crate::output::compression;
+use crate::output::error::{LogError, report};
+use chrono::{DateTime, Local};
+
+use crate::output::rotation::{RotationPeriod, RotationPolicy, dated_path, remove_expired, rotate_by_size};
+
+/// A global static variable that holds the open log files, keyed by their absolute path.
+static FILE_WRITERS: LazyLock<Mutex<HashMap<PathBuf, Arc<Mutex<FileWriter>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Whether the records are buffered, instead of written to the file right away.
+static BUFFERED: AtomicBool = AtomicBool::new(false);
+
+/// A buffered handle on an open log file.
+struct FileWriter {
+    /// The path of the destination.
+    base: PathBuf,
+    /// The path of the open file, the dated file with a time based rotation, `base` otherwise.
+    path: PathBuf,
+    /// The open file, `None` until the first record and after a failed rotation.
+    writer: Option<BufWriter<File>>,
+    /// The size of the file, including the buffered bytes.
+    size: u64,
+    /// When to switch to the next dated file, with a time based rotation.
+    rollover: Option<DateTime<Local>>,
+    /// The rotation policy applied to the previous record.
+    rotation: RotationPolicy,
+    /// The generation of the configuration `rotation` was adopted in, `0` before the first record.
+    rotation_generation: u64,
+    /// The generation of the configuration a conflicting policy was last reported in.
+    conflict_generation: u64,
+}
+
+impl FileWriter {
+    /// Creates the handle of the destination, the file is opened on the first record.
+    fn new(path: &Path) -> Self {
+        FileWriter {
+            base: path.to_path_buf(),
+            path: path.to_path_buf(),
+            writer: None,
+            size: 0,
+            rollover: None,
+            rotation: RotationPolicy::Never,
+            rotation_generation: 0,
+            conflict_generation: 0,
+        }
+    }
+
+    /// Opens the file at `self.path` in append mode, replacing the current handle.
+    fn reopen(&mut self) -> Result<&mut BufWriter<File>, LogError> {
+        let open_error = |source| LogError::Open {
+            path: self.path.clone(),
+            source,
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(open_error)?;
+        self.size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        Ok(self.writer.insert(BufWriter::new(file)))
+    }
+
+    /// Writes a line to the buffer, rotating the file first if the policy requires it, and flushing
+    /// the buffer if requested.
+    fn write_line(
+        &mut self,
+        line: &str,
+        flush: bool,
+        rotation: &RotationPolicy,
+        compress: bool,
+    ) -> Result<(), LogError> {
+        self.adopt_rotation(rotation);
+        let rotation = &self.rotation.clone();
+
+        match rotation {
+            RotationPolicy::Time {
+                period,
+                pattern,
+                max_age,
+            } => {
+                let now = Local::now();
+                if self.rollover.is_none_or(|rollover| now >= rollover)
+                    && let Err(error) = self.roll_over_by_time(&now, *period, pattern.as_deref(), *max_age, compress)
+                {
+                    report(error);
+                }
+            }
+            // Back to the destination path if the policy was time based
+            _ if self.path != self.base => {
+                self.rollover = None;
+                self.switch_to(self.base.clone())?;
+            }
+            _ => {}
+        }
+
+        if self.writer.is_none() {
+            self.reopen()?;
+        }
+
+        let line_size = line.len() as u64 + 1;
+        if let RotationPolicy::Size { max_bytes, keep } = rotation
+            && self.size > 0
+            && self.size + line_size > *max_bytes
+            && let Err(error) = self.rotate(|path| archive_by_size(path, *keep, compress))
+        {
+            // Keep writing the record, to the current file if it could not be archived
+            report(error);
+        }
+
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => self.reopen()?,
+        };
+        if let Err(source) = writeln!(writer, "{}", line) {
+            return Err(LogError::Write {
+                path: self.path.clone(),
+                source,
+            });
+        }
+        self.size += line_size;
+
+        if flush {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Adopts the policy of the first record written since the last configuration change. A changed
+    /// policy takes effect immediately, not at the next rollover of the previous one. Within the same
+    /// configuration, a different policy comes from another destination sharing the path: the first
+    /// policy is kept, instead of switching back and forth on every record, and the conflict reported.
+    fn adopt_rotation(&mut self, rotation: &RotationPolicy) {
+        let generation = snapshot::generation();
+        if generation != self.rotation_generation {
+            self.rotation_generation = generation;
+            if *rotation != self.rotation {
+                self.rotation = rotation.clone();
+                self.rollover = None;
+            }
+        } else if *rotation != self.rotation && generation != self.conflict_generation {
+            self.conflict_generation = generation;
+            report(LogError::Rotate {
+                path: self.base.clone(),
+                source: io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "destinations sharing this file have conflicting rotation policies, keeping {:?} and ignoring {:?}",
+                        self.rotation, rotation
+                    ),
+                ),
+            });
+        }
+    }
+
+    /// Switches to the dated file of the period containing `now`, then deletes the expired files.
+    fn roll_over_by_time(
+        &mut self,
+        now: &DateTime<Local>,
+        period: RotationPeriod,
+        pattern: Option<&str>,
+        max_age: Option<std::time::Duration>,
+        compress: bool,
+    ) -> Result<(), LogError> {
+        self.rollover = Some(period.next_rollover(now));
+        let base = self.base.clone();
+        let rotate_error = |source| LogError::Rotate {
+            path: base.clone(),
+            source,
+        };
+
+        let path = dated_path(&base, period, pattern, now).map_err(rotate_error)?;
+        if path != self.path {
+            // The previous dated file is complete, unless it is the destination path opened before the first rollover
+            let previous = self.path.clone();
+            self.switch_to(path)?;
+            if previous != base {
+                compress_in_background(previous, compress);
+            }
+        }
+
+        match max_age {
+            Some(max_age) => remove_expired(&base, period, pattern, max_age, &self.path).map_err(rotate_error),
+            None => Ok(()),
+        }
+    }
+
+    /// Closes the current file and opens the file at `path`.
+    fn switch_to(&mut self, path: PathBuf) -> Result<(), LogError> {
+        self.flush()?;
+        self.writer = None;
+        self.path = path;
+        self.reopen().map(|_| ())
+    }
+
+    /// Closes the file, moves it away with `archive`, and opens a new file at the same path.
+    /// If `archive` fails, the current file is reopened and keeps growing.
+    fn rotate(&mut self, archive: impl FnOnce(&Path) -> io::Result<()>) -> Result<(), LogError> {
+        self.flush()?;
+        // The handle is closed before renaming, as some platforms cannot rename open files
+        self.writer = None;
+
+        let archived = archive(&self.path).map_err(|source| LogError::Rotate {
+            path: self.path.clone(),
+            source,
+        });
+        self.reopen()?;
+        archived
+    }
+
+    /// Writes the buffer to the file.
+    fn flush(&mut self) -> Result<(), LogError> {
+        match self.writer.as_mut() {
+            Some(writer) => writer.flush().map_err(|source| LogError::Flush {
+                path: self.path.clone(),
+                source,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Archives the file with the size based rotation, compressing the archive if requested.
+fn archive_by_size(path: &Path, keep: usize, compress: bool) -> io::Result<()> {
+    #[cfg(feature = "compression")]
+    if compress {
+        return compression::rotate_by_size_compressed(path, keep);
+    }
+    #[cfg(not(feature = "compression"))]
+    let _ = compress;
+    rotate_by_size(path, keep)
+}
+
+/// Queues the compression of a complete dated file, if requested.
+fn compress_in_background(path: PathBuf, compress: bool) {
+    #[cfg(feature = "compression")]
+    if compress {
+        compression::compress_in_background(path);
+    }
+    #[cfg(not(feature = "compression"))]
+    let _ = (path, compress);
+}
+
+/// Returns the shared handle of the file, creating it if this is its first record.
+fn writer(path: &Path) -> Arc<Mutex<FileWriter>> {
+    let key = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut writers = FILE_WRITERS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(writer) = writers.get(&key) {
+        return writer.clone();
+    }
+
+    let writer = Arc::new(Mutex::new(FileWriter::new(path)));
+    writers.insert(key, writer.clone());
+    writer
+}
+
+/// Writes a line to the log file at `path`, through its shared buffered handle.
+/// Errors are routed to the error handler.
+///
+/// # Arguments
+///
+/// * `path` - The path of the log file.
+/// * `line` - The line to write, without the trailing newline.
+/// * `flush` - Whether to write the buffer to the file right away, always the case unless buffering is
+///   enabled with [`set_buffered`].
+/// * `rotation` - The rotation policy to apply before writing.
+/// * `compress` - Whether to compress the rotated files, requires the `compression` feature.
+pub fn write_line(path: impl AsRef<Path>, line: &str, flush: bool, rotation: &RotationPolicy, compress: bool) {
+    let writer = writer(path.as_ref());
+    let flush = flush || !BUFFERED.load(Ordering::Relaxed);
+    let result = writer
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .write_line(line, flush, rotation, compress);
+
+    if let Err(error) = result {
+        report(error);
+    }
+}
+
+/// Enables or disables the buffering of the log files, disabled by default. Disabling it writes the
+/// buffers of all the open log files to disk.
+///
+/// **Warning**: with buffering enabled, the records still buffered when the process exits are lost,
+/// unless [`Logger::flush`](crate::Logger::flush) is called or the guard returned by
+/// [`Logger::init`](crate::Logger::init) is dropped first.
+pub fn set_buffered(buffered: bool) {
+    BUFFERED.store(buffered, Ordering::Relaxed);
+    if !buffered {
+        flush_all();
+    }
+}
+
+/// Returns whether the log files are buffered.
+pub fn buffered() -> bool {
+    BUFFERED.load(Ordering::Relaxed)
+}
+
+/// Writes the buffers of all the open log files to disk. Errors are routed to the error handler.
+pub fn flush_all() {
+    // Collect the handles first so the registry is not locked while writing to disk
+    let writers: Vec<_> = FILE_WRITERS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .values()
+        .cloned()
+        .collect();
+
+    for writer in writers {
+        if let Err(error) = writer.lock().unwrap_or_else(PoisonError::into_inner).flush() {
+            report(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::rotation::archive_path;
+    use std::fs;
+
+    /// Creates an empty directory for the test in the temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("log_x-file_writer-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Returns the content of the file, `None` if it does not exist.
+    fn read(path: impl AsRef<Path>) -> Option<String> {
+        fs::read_to_string(path).ok()
+    }
+
+    #[test]
+    fn size_rollover_at_limit() {
+        let dir = temp_dir("size");
+        let path = dir.join("app.log");
+        let rotation = RotationPolicy::Size { max_bytes: 20, keep: 3 };
+        let mut writer = FileWriter::new(&path);
+
+        // 10 bytes per line with the newline, the third line would exceed 20 bytes
+        writer.write_line("record 1", true, &rotation, false).unwrap();
+        writer.write_line("record 2", true, &rotation, false).unwrap();
+        assert_eq!(read(&path).as_deref(), Some("record 1\nrecord 2\n"));
+        assert_eq!(read(archive_path(&path, 1)), None);
+
+        writer.write_line("record 3", true, &rotation, false).unwrap();
+        assert_eq!(read(&path).as_deref(), Some("record 3\n"));
+        assert_eq!(read(archive_path(&path, 1)).as_deref(), Some("record 1\nrecord 2\n"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn size_rollover_prunes_beyond_keep() {
+        let dir = temp_dir("keep");
+        let path = dir.join("app.log");
+        // Every record rolls the previous one over
+        let rotation = RotationPolicy::Size { max_bytes: 10, keep: 2 };
+        let mut writer = FileWriter::new(&path);
+        for index in 1..=5 {
+            writer
+                .write_line(&format!("record {}", index), true, &rotation, false)
+                .unwrap();
+        }
+
+        assert_eq!(read(&path).as_deref(), Some("record 5\n"));
+        assert_eq!(read(archive_path(&path, 1)).as_deref(), Some("record 4\n"));
+        assert_eq!(read(archive_path(&path, 2)).as_deref(), Some("record 3\n"));
+        assert_eq!(read(archive_path(&path, 3)), None);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
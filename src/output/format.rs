@@ -0,0 +1,134 @@
+//! This module defines the output formats available for file destinations.
+//!
+//! # Enums
+//!
+//! - `LogFormat`: The layout of each line written to a log file.
+//!
+//! # Formats
+//!
+//! - `LogFormat::Text`: The bracketed human readable layout, also used on stdout.
+//! - `LogFormat::Json`: One JSON object per line (JSON Lines), to be ingested directly by log shippers.
+//!   Each object contains the `timestamp`, `level`, `module`, `file`, `line` and `message` keys, a
+//!   `fields` object holding the structured fields if any, and a `source` key (`file:line`) when
+//!   paranoia is on.
+//!
+//! ```text
+//! {"timestamp":"2025-08-29 10:22:11","level":"INFO","module":"app::db","file":"src/db.rs","line":42,"message":"query done","fields":{"rows":3}}
+//! ```
+use std::fmt::Write;
+
+use crate::LogMetadata;
+use crate::fields::FieldValue;
+
+/// The layout of each line written to a log file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub enum LogFormat {
+    /// The bracketed human readable layout.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+/// Renders the metadata as a single line JSON object.
+///
+/// # Arguments
+///
+/// * `metadata` - A reference to the `LogMetadata` to be rendered.
+/// * `paranoia` - Whether to add the `source` key with the file and line of the log entry.
+///
+/// # Examples
+///
+/// ```
+/// use log_x::LogMetadata;
+/// use log_x::fields::Field;
+/// use log_x::loggers::log_levels::LogLevel;
+/// use log_x::output::format::to_json;
+///
+/// let metadata = LogMetadata::new("2025-08-29 10:22:11", LogLevel::Warn, "src/db.rs", "app::db", 42, "say \"hi\"\n")
+///     .with_fields(vec![Field::new("rows", 3), Field::new("table", "users")]);
+///
+/// assert_eq!(
+///     to_json(&metadata, true),
+///     r#"{"timestamp":"2025-08-29 10:22:11","level":"WARN","module":"app::db","file":"src/db.rs","line":42,"message":"say \"hi\"\n","fields":{"rows":3,"table":"users"},"source":"src/db.rs:42"}"#
+/// );
+/// ```
+pub fn to_json(metadata: &LogMetadata, paranoia: bool) -> String {
+    let mut json = String::with_capacity(128 + metadata.message().len());
+    json.push_str("{\"timestamp\":");
+    push_json_string(&mut json, &metadata.timestamp());
+    json.push_str(",\"level\":");
+    push_json_string(&mut json, &metadata.level().to_string());
+    json.push_str(",\"module\":");
+    push_json_string(&mut json, metadata.module());
+    json.push_str(",\"file\":");
+    push_json_string(&mut json, metadata.file());
+    let _ = write!(json, ",\"line\":{}", metadata.line());
+    json.push_str(",\"message\":");
+    push_json_string(&mut json, metadata.message());
+
+    if !metadata.fields().is_empty() {
+        json.push_str(",\"fields\":{");
+        for (index, field) in metadata.fields().iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            push_json_string(&mut json, field.key());
+            json.push(':');
+            push_json_value(&mut json, field.value());
+        }
+        json.push('}');
+    }
+
+    if paranoia {
+        json.push_str(",\"source\":");
+        push_json_string(&mut json, &format!("{}:{}", metadata.file(), metadata.line()));
+    }
+
+    json.push('}');
+    json
+}
+
+/// Appends a field value, keeping numbers and booleans as JSON numbers and booleans.
+fn push_json_value(json: &mut String, value: &FieldValue) {
+    match value {
+        FieldValue::Str(value) | FieldValue::Display(value) | FieldValue::Debug(value) => {
+            push_json_string(json, value);
+        }
+        FieldValue::I64(value) => {
+            let _ = write!(json, "{}", value);
+        }
+        FieldValue::U64(value) => {
+            let _ = write!(json, "{}", value);
+        }
+        // JSON has no representation for NaN and infinities
+        FieldValue::F64(value) if value.is_finite() => {
+            let _ = write!(json, "{}", value);
+        }
+        FieldValue::F64(value) => push_json_string(json, &value.to_string()),
+        FieldValue::Bool(value) => {
+            let _ = write!(json, "{}", value);
+        }
+    }
+}
+
+/// Appends a quoted JSON string, escaping quotes, backslashes and control characters.
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '\u{08}' => json.push_str("\\b"),
+            '\u{0C}' => json.push_str("\\f"),
+            c if c < '\u{20}' => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
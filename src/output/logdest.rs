@@ -1,23 +1,132 @@
-use std::path::Path;
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
 
 use crate::LogMetadata;
-use crate::loggers::global_logger::DefaultLogger;
-use crate::loggers::mod_logger::ModLogger;
-use crate::terminal::colors::Colorize;
+use crate::loggers::log_levels::LogLevel;
+use crate::output::file_writer;
+use crate::output::format::{LogFormat, to_json};
+use crate::output::rotation::RotationPolicy;
+use crate::output::sink::{ConsoleOutput, ConsoleSink, FileSink, Sink};
+use crate::output::template::Template;
+use crate::output::timestamp::TimestampFormat;
+use crate::terminal::colors::{Colorize, Stream, colors_enabled, with_stream};
 
-/// Represents a logging destination, which can be stdout, a file, or both.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+/// Identifies a sink of a `LogDestination`.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+enum SinkId {
+    /// The console output, with the streams it prints to.
+    Console(ConsoleOutput),
+    /// The file output, with the path of the file.
+    File(String),
+    /// A custom sink, with its name.
+    Custom(String),
+}
+
+/// A sink of a `LogDestination`, shared by the clones of the destination.
+#[derive(Clone)]
+struct SinkEntry {
+    id: SinkId,
+    /// The most verbose level written to the sink.
+    level: LogLevel,
+    sink: Arc<dyn Sink>,
+}
+
+/// Represents a logging destination: the sinks the records are written to, stdout, any number of
+/// files, each with its own level, and any number of custom sinks, see [`crate::output::sink`].
+/// The format, rotation policy and compression of the files are shared by all the files.
+///
+/// Every sink has its own level: a record is written to the sinks whose level is at least as
+/// verbose as the record, provided the level of the logger enables it in the first place.
+///
+/// ```
+/// use log_x::loggers::log_levels::LogLevel;
+/// use log_x::output::logdest::LogDestination;
+///
+/// let mut destination = LogDestination::default();
+/// destination.set_stdout_level(LogLevel::Info);
+/// assert_eq!(destination.max_level(), LogLevel::Info);
+///
+/// destination.add_file("trace.log", LogLevel::Trace);
+/// assert_eq!(destination.max_level(), LogLevel::Trace);
+/// ```
+#[derive(Clone)]
 pub struct LogDestination {
-    stdout: bool,
-    file: Option<String>,
+    sinks: Vec<SinkEntry>,
+    file_format: LogFormat,
+    file_rotation: RotationPolicy,
+    file_compression: bool,
+    template: Option<Arc<Template>>,
+    timestamp_format: TimestampFormat,
+    timestamp_utc: bool,
 }
 
 impl Default for LogDestination {
     /// Creates a default `LogDestination` that logs to stdout and not to a file.
     fn default() -> Self {
-        LogDestination {
-            stdout: true,
-            file: None,
+        LogDestination::new(true, None)
+    }
+}
+
+impl Debug for LogDestination {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let sinks: Vec<(&SinkId, LogLevel)> = self.sinks.iter().map(|entry| (&entry.id, entry.level)).collect();
+        f.debug_struct("LogDestination")
+            .field("sinks", &sinks)
+            .field("file_format", &self.file_format)
+            .field("file_rotation", &self.file_rotation)
+            .field("file_compression", &self.file_compression)
+            .field("template", &self.template.as_ref().map(|template| template.source()))
+            .field("timestamp_format", &self.timestamp_format)
+            .field("timestamp_utc", &self.timestamp_utc)
+            .finish()
+    }
+}
+
+impl PartialEq for LogDestination {
+    /// Two destinations are equal if they have the same settings and share the same sinks.
+    fn eq(&self, other: &Self) -> bool {
+        self.file_format == other.file_format
+            && self.file_rotation == other.file_rotation
+            && self.file_compression == other.file_compression
+            && self.template == other.template
+            && self.timestamp_format == other.timestamp_format
+            && self.timestamp_utc == other.timestamp_utc
+            && self.sinks.len() == other.sinks.len()
+            && self.sinks.iter().zip(&other.sinks).all(|(a, b)| {
+                a.id == b.id && a.level == b.level && std::ptr::addr_eq(Arc::as_ptr(&a.sink), Arc::as_ptr(&b.sink))
+            })
+    }
+}
+
+impl PartialOrd for LogDestination {
+    /// Destinations are ordered on their settings, then on the ids and levels of their sinks.
+    /// Destinations differing only by the instances of their sinks are not comparable.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        let key = |destination: &LogDestination| {
+            (
+                destination.file_format,
+                destination.file_rotation.clone(),
+                destination.file_compression,
+                destination
+                    .template
+                    .as_ref()
+                    .map(|template| template.source().to_string()),
+                destination.timestamp_format.clone(),
+                destination.timestamp_utc,
+                destination
+                    .sinks
+                    .iter()
+                    .map(|entry| (entry.id.clone(), entry.level))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        match key(self).partial_cmp(&key(other)) {
+            Some(Ordering::Equal) => None,
+            ordering => ordering,
         }
     }
 }
@@ -30,37 +139,336 @@ impl LogDestination {
     /// * `stdout` - A boolean indicating whether to log to stdout.
     /// * `file` - An optional string specifying the file to log to.
     pub fn new(stdout: bool, file: Option<String>) -> Self {
-        LogDestination { stdout, file }
+        let mut destination = LogDestination {
+            sinks: Vec::new(),
+            file_format: LogFormat::Text,
+            file_rotation: RotationPolicy::Never,
+            file_compression: false,
+            template: None,
+            timestamp_format: TimestampFormat::Seconds,
+            timestamp_utc: false,
+        };
+        if stdout {
+            destination.log_to_stdout();
+        }
+        if let Some(file) = file {
+            destination.log_to_file(file);
+        }
+        destination
     }
 
-    /// Enables logging to stdout.
+    /// Enables logging to stdout, replacing the current console output.
     pub fn log_to_stdout(&mut self) {
-        self.stdout = true;
+        self.set_console(ConsoleOutput::Stdout);
+    }
+
+    /// Enables logging to stderr, replacing the current console output.
+    pub fn log_to_stderr(&mut self) {
+        self.set_console(ConsoleOutput::Stderr);
     }
 
-    /// Sets the file to log to.
+    /// Enables logging to the console, `Warn` and `Error` records to stderr and the other ones to
+    /// stdout, replacing the current console output.
+    pub fn log_to_split_console(&mut self) {
+        self.set_console(ConsoleOutput::Split);
+    }
+
+    /// Returns the console streams logged to, `None` if not logging to the console.
+    pub fn console_output(&self) -> Option<ConsoleOutput> {
+        self.sinks.iter().find_map(|entry| match entry.id {
+            SinkId::Console(output) => Some(output),
+            _ => None,
+        })
+    }
+
+    /// Sets the file to log to, replacing all the file destinations.
+    ///
+    /// Records are written to disk right away, unless buffering is enabled, see
+    /// [`file_writer`](crate::output::file_writer): buffered records are lost at exit unless
+    /// [`Logger::flush`](crate::Logger::flush) is called first.
     ///
     /// # Arguments
     ///
     /// * `file` - A string specifying the file to log to.
     pub fn log_to_file(&mut self, file: String) {
-        self.file = Some(file);
+        self.remove_file();
+        self.add_file(file, LogLevel::Trace);
     }
 
-    /// Disables logging to stdout.
+    /// Adds a file to log to, keeping the other file destinations. Only the records at `level` or
+    /// more severe are written to it. Adding a file already logged to updates its level.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - A string specifying the file to log to.
+    /// * `level` - The most verbose level written to the file.
+    pub fn add_file(&mut self, file: impl Into<String>, level: LogLevel) {
+        let file = file.into();
+        let sink = Arc::new(self.file_sink(&file));
+        let id = SinkId::File(file);
+        match self.sinks.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.level = level;
+                entry.sink = sink;
+            }
+            None => self.sinks.push(SinkEntry { id, level, sink }),
+        }
+    }
+
+    /// Removes the file destination of the given path, keeping the other ones.
+    pub fn remove_file_at(&mut self, file: &str) {
+        self.sinks
+            .retain(|entry| !matches!(&entry.id, SinkId::File(path) if path == file));
+    }
+
+    /// Returns the paths of the files logged to, with the most verbose level written to each.
+    pub fn files(&self) -> Vec<(&str, LogLevel)> {
+        self.sinks
+            .iter()
+            .filter_map(|entry| match &entry.id {
+                SinkId::File(path) => Some((path.as_str(), entry.level)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sets the format of the lines written to the log file.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The `LogFormat` to use for the file destination.
+    pub fn set_file_format(&mut self, format: LogFormat) {
+        self.file_format = format;
+        self.refresh_file_sink();
+    }
+
+    /// Returns the format of the lines written to the log file.
+    pub fn file_format(&self) -> LogFormat {
+        self.file_format
+    }
+
+    /// Sets the rotation policy of the log file.
+    ///
+    /// # Arguments
+    ///
+    /// * `rotation` - The `RotationPolicy` to apply to the file destination.
+    pub fn set_file_rotation(&mut self, rotation: RotationPolicy) {
+        self.file_rotation = rotation;
+        self.refresh_file_sink();
+    }
+
+    /// Returns the rotation policy of the log file.
+    pub fn file_rotation(&self) -> &RotationPolicy {
+        &self.file_rotation
+    }
+
+    /// Sets whether the rotated log files are compressed with gzip, see [`crate::output::compression`].
+    ///
+    /// # Arguments
+    ///
+    /// * `compression` - A boolean indicating whether to compress the rotated files.
+    #[cfg(feature = "compression")]
+    pub fn set_file_compression(&mut self, compression: bool) {
+        self.file_compression = compression;
+        self.refresh_file_sink();
+    }
+
+    /// Returns whether the rotated log files are compressed with gzip.
+    pub fn file_compression(&self) -> bool {
+        self.file_compression
+    }
+
+    /// Sets the template of the console and text file lines, replacing the built-in layout, see
+    /// [`crate::output::template`].
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The parsed template.
+    pub fn set_template(&mut self, template: Template) {
+        self.template = Some(Arc::new(template));
+    }
+
+    /// Restores the built-in layout of the console and text file lines.
+    pub fn remove_template(&mut self) {
+        self.template = None;
+    }
+
+    /// Returns the template of the console and text file lines, if any.
+    pub fn template(&self) -> Option<&Template> {
+        self.template.as_deref()
+    }
+
+    /// Sets the format of the timestamps written to the sinks, see [`crate::output::timestamp`].
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The precision of the timestamps, RFC 3339, or a custom `chrono` format.
+    pub fn set_timestamp_format(&mut self, format: TimestampFormat) {
+        self.timestamp_format = format;
+    }
+
+    /// Returns the format of the timestamps written to the sinks.
+    pub fn timestamp_format(&self) -> &TimestampFormat {
+        &self.timestamp_format
+    }
+
+    /// Sets whether the timestamps are written in UTC rather than in local time.
+    pub fn set_timestamp_utc(&mut self, utc: bool) {
+        self.timestamp_utc = utc;
+    }
+
+    /// Returns whether the timestamps are written in UTC.
+    pub fn timestamp_utc(&self) -> bool {
+        self.timestamp_utc
+    }
+
+    /// Attaches a custom sink, replacing the sink of the same name if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the sink, used to remove it.
+    /// * `sink` - The sink receiving the records.
+    pub fn add_sink(&mut self, name: impl Into<String>, sink: impl Sink + 'static) {
+        let id = SinkId::Custom(name.into());
+        let sink: Arc<dyn Sink> = Arc::new(sink);
+        match self.sinks.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => entry.sink = sink,
+            None => self.sinks.push(SinkEntry {
+                id,
+                level: LogLevel::Trace,
+                sink,
+            }),
+        }
+    }
+
+    /// Detaches the custom sink of the given name.
+    pub fn remove_sink(&mut self, name: &str) {
+        self.sinks
+            .retain(|entry| !matches!(&entry.id, SinkId::Custom(custom) if custom == name));
+    }
+
+    /// Returns whether a custom sink of the given name is attached.
+    pub fn has_sink(&self, name: &str) -> bool {
+        self.sinks
+            .iter()
+            .any(|entry| matches!(&entry.id, SinkId::Custom(custom) if custom == name))
+    }
+
+    /// Sets the most verbose level printed to the console, if logging to stdout or stderr.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The most verbose level printed to the console.
+    pub fn set_stdout_level(&mut self, level: LogLevel) {
+        if let Some(entry) = self
+            .sinks
+            .iter_mut()
+            .find(|entry| matches!(entry.id, SinkId::Console(_)))
+        {
+            entry.level = level;
+        }
+    }
+
+    /// Sets the most verbose level written to the custom sink of the given name, if attached.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the sink.
+    /// * `level` - The most verbose level written to the sink.
+    pub fn set_sink_level(&mut self, name: &str, level: LogLevel) {
+        self.set_level(&SinkId::Custom(name.to_string()), level);
+    }
+
+    /// Returns the most verbose level written to at least one sink, `Off` without any sink.
+    pub fn max_level(&self) -> LogLevel {
+        self.sinks
+            .iter()
+            .fold(LogLevel::Off, |max, entry| match entry.level > max {
+                true => entry.level,
+                false => max,
+            })
+    }
+
+    /// Disables logging to the console, whether to stdout, stderr or both.
     pub fn remove_stdout(&mut self) {
-        self.stdout = false;
+        self.sinks.retain(|entry| !matches!(entry.id, SinkId::Console(_)));
     }
 
-    /// Removes the file logging destination.
+    /// Removes all the file destinations.
     pub fn remove_file(&mut self) {
-        self.file = None;
+        self.sinks.retain(|entry| !matches!(entry.id, SinkId::File(_)));
     }
 
-    /// Disables all logging destinations.
+    /// Disables all logging destinations, including the custom sinks.
     pub fn silent(&mut self) {
-        self.stdout = false;
-        self.file = None;
+        self.sinks.clear();
+    }
+
+    /// Writes the buffered records of the sinks, if any.
+    pub fn flush(&self) {
+        for entry in &self.sinks {
+            entry.sink.flush();
+        }
+    }
+
+    /// Sets the console streams logged to, keeping the level of the console output if any.
+    fn set_console(&mut self, output: ConsoleOutput) {
+        let sink: Arc<dyn Sink> = Arc::new(ConsoleSink::new(output));
+        match self
+            .sinks
+            .iter_mut()
+            .find(|entry| matches!(entry.id, SinkId::Console(_)))
+        {
+            Some(entry) => {
+                entry.id = SinkId::Console(output);
+                entry.sink = sink;
+            }
+            None => self.sinks.insert(
+                0,
+                SinkEntry {
+                    id: SinkId::Console(output),
+                    level: LogLevel::Trace,
+                    sink,
+                },
+            ),
+        }
+    }
+
+    /// Attaches the custom sinks of `previous` not attached to this destination, with their levels,
+    /// so that reloading the configuration keeps the sinks attached in code.
+    #[cfg(feature = "config")]
+    pub(crate) fn keep_custom_sinks(&mut self, previous: &LogDestination) {
+        for entry in &previous.sinks {
+            if matches!(entry.id, SinkId::Custom(_)) && !self.sinks.iter().any(|current| current.id == entry.id) {
+                self.sinks.push(entry.clone());
+            }
+        }
+    }
+
+    /// Sets the most verbose level written to the sink of the given id.
+    fn set_level(&mut self, id: &SinkId, level: LogLevel) {
+        if let Some(entry) = self.sinks.iter_mut().find(|entry| entry.id == *id) {
+            entry.level = level;
+        }
+    }
+
+    /// Returns the file sink of `file` with the file settings, shared by all the file destinations.
+    fn file_sink(&self, file: &str) -> FileSink {
+        let sink = FileSink::new(file)
+            .with_format(self.file_format)
+            .with_rotation(self.file_rotation.clone());
+        #[cfg(feature = "compression")]
+        let sink = sink.with_compression(self.file_compression);
+        sink
+    }
+
+    /// Rebuilds the file sink after a change of the file settings.
+    fn refresh_file_sink(&mut self) {
+        for index in 0..self.sinks.len() {
+            if let SinkId::File(file) = &self.sinks[index].id {
+                self.sinks[index].sink = Arc::new(self.file_sink(file));
+            }
+        }
     }
 }
 
@@ -70,12 +478,10 @@ impl LogDestination {
 ///
 /// * `metadata` - A reference to the `LogMetadata` to be logged.
 pub fn log_to_destination(metadata: &LogMetadata) {
-    if metadata.log_destinations.stdout {
-        log_to_stdout(metadata);
-    }
-
-    if let Some(file) = &metadata.log_destinations.file {
-        log_to_file(metadata, file);
+    for entry in &metadata.log_destinations.sinks {
+        if metadata.level() <= entry.level {
+            entry.sink.log(metadata);
+        }
     }
 }
 
@@ -85,33 +491,50 @@ pub fn log_to_destination(metadata: &LogMetadata) {
 ///
 /// * `metadata` - A reference to the `LogMetadata` to be logged.
 pub fn log_to_stdout(metadata: &LogMetadata) {
+    println!("{}", format_console(metadata, Stream::Stdout));
+}
+
+/// Logs the given metadata to stderr.
+///
+/// # Arguments
+///
+/// * `metadata` - A reference to the `LogMetadata` to be logged.
+pub fn log_to_stderr(metadata: &LogMetadata) {
+    eprintln!("{}", format_console(metadata, Stream::Stderr));
+}
+
+/// Renders the metadata as a console line, colorized if colors are enabled for the stream.
+fn format_console(metadata: &LogMetadata, stream: Stream) -> String {
+    with_stream(stream, || format_console_line(metadata, colors_enabled(stream)))
+}
+
+/// Renders the metadata as a console line.
+fn format_console_line(metadata: &LogMetadata, colors: bool) -> String {
+    if let Some(template) = metadata.log_destinations.template() {
+        return template.render(metadata, colors);
+    }
+
+    // The escape codes of the level take 9 characters but no column
+    let width = match colors {
+        true => 36,
+        false => 27,
+    };
     let timestamp = format!("{} - {}", metadata.timestamp(), metadata.level().colorized());
     let paranoia = format!(" | File: {}:{} | ", metadata.file(), metadata.line());
 
-    let paranoia = match metadata.logging_from_module {
-        true => {
-            if ModLogger::get_mod_paranoia(metadata.module.as_str()) {
-                paranoia.magenta()
-            } else {
-                "".to_string()
-            }
-        }
-        false => {
-            if DefaultLogger::paranoia() {
-                paranoia.magenta()
-            } else {
-                "".to_string()
-            }
-        }
+    let paranoia = match paranoia_enabled(metadata) {
+        true => paranoia.magenta(),
+        false => "".to_string(),
     };
 
-    println!(
-        "[{:^36}][{}] {}{}",
+    format!(
+        "[{:^width$}][{}] {}{}{}",
         timestamp,
         metadata.module().gray(),
         metadata.message(),
+        render_fields(metadata),
         paranoia
-    );
+    )
 }
 
 /// Logs the given metadata to a file.
@@ -121,54 +544,79 @@ pub fn log_to_stdout(metadata: &LogMetadata) {
 /// * `metadata` - A reference to the `LogMetadata` to be logged.
 /// * `file` - The file to log to.
 pub fn log_to_file(metadata: &LogMetadata, file: impl Into<String>) {
+    write_record(metadata, file.into(), &format_text(metadata));
+}
+
+/// Renders the metadata as a text line of a log file.
+pub(crate) fn format_text(metadata: &LogMetadata) -> String {
+    if let Some(template) = metadata.log_destinations.template() {
+        return template.render(metadata, false);
+    }
+
     let timestamp = format!("{} - {}", metadata.timestamp(), metadata.level());
     let paranoia = format!(" | File: {} | Line: {} | ", metadata.file(), metadata.line());
 
-    let paranoia = match metadata.logging_from_module {
-        true => {
-            if ModLogger::get_mod_paranoia(metadata.module.as_str()) {
-                paranoia
-            } else {
-                "".to_string()
-            }
-        }
-        false => {
-            if DefaultLogger::paranoia() {
-                paranoia
-            } else {
-                "".to_string()
-            }
-        }
+    let paranoia = match paranoia_enabled(metadata) {
+        true => paranoia,
+        false => "".to_string(),
     };
 
-    let payload = format!(
-        "[{:^27}][{}] {}{}",
+    format!(
+        "[{:^27}][{}] {}{}{}",
         timestamp,
         metadata.module(),
         metadata.message(),
+        render_fields(metadata),
         paranoia
+    )
+}
+
+/// Logs the given metadata to a file as a single line JSON object.
+///
+/// # Arguments
+///
+/// * `metadata` - A reference to the `LogMetadata` to be logged.
+/// * `file` - The file to log to.
+pub fn log_to_json_file(metadata: &LogMetadata, file: impl Into<String>) {
+    write_record(metadata, file.into(), &to_json(metadata, paranoia_enabled(metadata)));
+}
+
+/// Writes a rendered record through the shared buffered handle of the file.
+/// `Warn` and `Error` records are written to disk right away, the others too unless buffering is enabled.
+fn write_record(metadata: &LogMetadata, file: String, payload: &str) {
+    file_writer::write_line(
+        file,
+        payload,
+        metadata.level() <= LogLevel::Warn,
+        &metadata.log_destinations.file_rotation,
+        metadata.log_destinations.file_compression,
     );
+}
+
+/// Returns the paranoia setting of the logger the metadata is logged from.
+pub(crate) fn paranoia_enabled(metadata: &LogMetadata) -> bool {
+    metadata.paranoia
+}
 
-    write_to_file(file, payload);
+/// Renders the structured fields of the metadata as ` key=value` pairs, to be appended to the message.
+fn render_fields(metadata: &LogMetadata) -> String {
+    let mut rendered = String::new();
+    for field in metadata.fields() {
+        if !rendered.is_empty() || !metadata.message().is_empty() {
+            rendered.push(' ');
+        }
+        rendered.push_str(&field.to_string());
+    }
+    rendered
 }
 
-/// Writes a message to a file.
+/// Writes a message to a file, through the shared handle of the file, and writes it to disk right away.
+/// Errors are routed to the error handler, see [`crate::output::error`].
 ///
 /// # Arguments
 ///
 /// * `filename` - The name of the file to write to.
 /// * `message` - The message to write to the file.
 pub fn write_to_file(filename: impl Into<String>, message: impl Into<String>) {
-    use std::fs::OpenOptions;
-    use std::io::Write;
-    let filename = filename.into();
-    let file = Path::new(&filename);
-    match OpenOptions::new().create(true).append(true).open(file) {
-        Ok(mut file) => {
-            writeln!(file, "{}", message.into()).unwrap();
-        }
-        Err(e) => {
-            eprintln!("Error opening file: {} , {}", &file.display(), e);
-        }
-    }
+    file_writer::write_line(filename.into(), &message.into(), true, &RotationPolicy::Never, false);
 }
@@ -0,0 +1,374 @@
+//! This module defines the rotation policies of the log files.
+//!
+//! # Enums
+//!
+//! - `RotationPolicy`: When a log file is rolled over and how many archives are kept.
+//! - `RotationPeriod`: The period of the time based rotation.
+//!
+//! # Size based rotation
+//!
+//! With `RotationPolicy::Size`, a record that would grow the file beyond `max_bytes` first rolls the
+//! file over: `app.log` is renamed to `app.log.1`, `app.log.1` to `app.log.2` and so on, the oldest
+//! archive beyond `keep` is deleted, and a new `app.log` is created. With `keep` set to `0` the file
+//! is simply truncated.
+//!
+//! # Time based rotation
+//!
+//! With `RotationPolicy::Time`, records are written to a dated file that changes every hour, day or
+//! week, at the start of the period in local time, using the same `chrono::Local` clock as the
+//! `timestamp!` macro. The dated file name is built from a `chrono` format `pattern`, placed in the
+//! directory of the destination path. Without pattern, the date is added to the file stem of the
+//! destination path: `logs/app.log` becomes `logs/app-2026-10-16-13.log` (hourly),
+//! `logs/app-2026-10-16.log` (daily) or `logs/app-2026-W42.log` (weekly).
+//!
+//! With `max_age` set, when the file changes, the files of the same pattern last modified more than
+//! `max_age` ago are deleted. Only the files whose whole name parses with the pattern are deleted,
+//! optionally followed by a `.N` index and the `.gz` extension of the compressed files: next to
+//! `app-2026-10-16.log`, `app-errors.log` is never deleted.
+//!
+//! With the `compression` feature, the rotated files can also be compressed with gzip, see the
+//! `compression` module.
+//!
+//! Rotation happens while holding the lock of the shared file handle (see
+//! [`file_writer`](crate::output::file_writer)), so the default logger and the module loggers writing
+//! to the same path never write to a file being rotated.
+//!
+//! ```
+//! use log_x::Logger;
+//! use log_x::loggers::global_logger::DefaultLoggerTrait;
+//! use log_x::output::rotation::{RotationPeriod, RotationPolicy};
+//! use std::time::Duration;
+//!
+//! Logger::log_to_file("app.log");
+//! // roll over every 10 MiB, keeping app.log.1 to app.log.5
+//! Logger::set_file_rotation(RotationPolicy::Size {
+//!     max_bytes: 10 * 1024 * 1024,
+//!     keep: 5,
+//! });
+//!
+//! // or write to one file per day, named like app-2026-10-16.log, deleting the files older than a month
+//! Logger::set_file_rotation(RotationPolicy::Time {
+//!     period: RotationPeriod::Daily,
+//!     pattern: None,
+//!     max_age: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+//! });
+//! ```
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::format::{Parsed, StrftimeItems, parse};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeDelta, TimeZone, Timelike};
+
+/// When a log file is rolled over and how many archives are kept.
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd)]
+pub enum RotationPolicy {
+    /// The log file grows unbounded.
+    #[default]
+    Never,
+    /// Roll over when the file would exceed `max_bytes`, keeping at most `keep` numbered archives.
+    Size { max_bytes: u64, keep: usize },
+    /// Write to a dated file changing every `period`, named after the `chrono` format `pattern`,
+    /// deleting the files older than `max_age`.
+    Time {
+        period: RotationPeriod,
+        pattern: Option<String>,
+        max_age: Option<Duration>,
+    },
+}
+
+/// The period of the time based rotation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub enum RotationPeriod {
+    /// A new file every hour.
+    Hourly,
+    /// A new file every day, at local midnight.
+    #[default]
+    Daily,
+    /// A new file every week, on Monday at local midnight.
+    Weekly,
+}
+
+impl RotationPeriod {
+    /// Returns the default `chrono` format of the date added to the file name.
+    pub fn date_format(&self) -> &'static str {
+        match self {
+            RotationPeriod::Hourly => "%Y-%m-%d-%H",
+            RotationPeriod::Daily => "%Y-%m-%d",
+            RotationPeriod::Weekly => "%G-W%V",
+        }
+    }
+
+    /// Returns the length of the period.
+    fn length(&self) -> TimeDelta {
+        match self {
+            RotationPeriod::Hourly => TimeDelta::hours(1),
+            RotationPeriod::Daily => TimeDelta::days(1),
+            RotationPeriod::Weekly => TimeDelta::weeks(1),
+        }
+    }
+
+    /// Returns the local time at which the period containing `now` starts.
+    fn start(&self, now: &DateTime<Local>) -> NaiveDateTime {
+        let date = now.date_naive();
+        let start = match self {
+            RotationPeriod::Hourly => date.and_hms_opt(now.hour(), 0, 0),
+            RotationPeriod::Daily => date.and_hms_opt(0, 0, 0),
+            RotationPeriod::Weekly => {
+                (date - TimeDelta::days(date.weekday().num_days_from_monday().into())).and_hms_opt(0, 0, 0)
+            }
+        };
+        start.unwrap_or_else(|| now.naive_local())
+    }
+
+    /// Returns the instant at which the period following the one containing `now` starts.
+    pub fn next_rollover(&self, now: &DateTime<Local>) -> DateTime<Local> {
+        let next = self.start(now) + self.length();
+        // The start of the next period may not exist in local time around DST changes, retry an hour later
+        Local
+            .from_local_datetime(&next)
+            .earliest()
+            .unwrap_or_else(|| *now + TimeDelta::hours(1))
+    }
+}
+
+/// Returns the default pattern of the dated file names: the date added to the file stem of `path`.
+pub fn default_pattern(path: &Path, period: RotationPeriod) -> String {
+    let escape = |name: &std::ffi::OsStr| name.to_string_lossy().replace('%', "%%");
+    let stem = path.file_stem().map(escape).unwrap_or_default();
+    match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, period.date_format(), escape(extension)),
+        None => format!("{}-{}", stem, period.date_format()),
+    }
+}
+
+/// Returns the path of the dated file to write to at `now`, in the directory of `path`.
+pub fn dated_path(
+    path: &Path,
+    period: RotationPeriod,
+    pattern: Option<&str>,
+    now: &DateTime<Local>,
+) -> io::Result<PathBuf> {
+    let default;
+    let pattern = match pattern {
+        Some(pattern) => pattern,
+        None => {
+            default = default_pattern(path, period);
+            &default
+        }
+    };
+
+    let mut name = String::new();
+    if write!(name, "{}", now.format(pattern)).is_err() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid file name pattern \"{}\"", pattern),
+        ));
+    }
+    Ok(path.with_file_name(name))
+}
+
+/// Deletes the files of the directory of `path` matching the pattern and last modified more than
+/// `max_age` ago, except `current`.
+pub(crate) fn remove_expired(
+    path: &Path,
+    period: RotationPeriod,
+    pattern: Option<&str>,
+    max_age: Duration,
+    current: &Path,
+) -> io::Result<()> {
+    let default;
+    let pattern = match pattern {
+        Some(pattern) => pattern,
+        None => {
+            default = default_pattern(path, period);
+            &default
+        }
+    };
+
+    let directory = match current.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let now = SystemTime::now();
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if entry.path() == current || !is_dated_file(&name.to_string_lossy(), pattern) {
+            continue;
+        }
+
+        let modified = entry.metadata()?.modified()?;
+        if now.duration_since(modified).is_ok_and(|age| age > max_age) {
+            remove_if_exists(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether `name` is a file written with the pattern: the whole name parses with the pattern,
+/// optionally followed by a `.N` index and the `.gz` extension of the compressed files.
+fn is_dated_file(name: &str, pattern: &str) -> bool {
+    let parses = |name: &str| parse(&mut Parsed::new(), name, StrftimeItems::new(pattern)).is_ok();
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    if parses(name) {
+        return true;
+    }
+    match name.rsplit_once('.') {
+        Some((dated, index)) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => parses(dated),
+        _ => false,
+    }
+}
+
+/// Returns the path of the numbered archive `index` of the log file, e.g. `app.log.1`.
+pub fn archive_path(path: &Path, index: usize) -> PathBuf {
+    let mut archive = OsString::from(path.as_os_str());
+    archive.push(format!(".{}", index));
+    PathBuf::from(archive)
+}
+
+/// Shifts the numbered archives of the log file and renames the file to `.1`, deleting the archives
+/// beyond `keep`. The log file must be closed before calling this function.
+pub(crate) fn rotate_by_size(path: &Path, keep: usize) -> io::Result<()> {
+    if keep == 0 {
+        return remove_if_exists(path);
+    }
+
+    remove_if_exists(&archive_path(path, keep))?;
+    for index in (1..keep).rev() {
+        rename_if_exists(&archive_path(path, index), &archive_path(path, index + 1))?;
+    }
+    rename_if_exists(path, &archive_path(path, 1))
+}
+
+/// Removes the file, ignoring a missing file.
+pub(crate) fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Renames the file, ignoring a missing source file.
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory for the test in the temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("log_x-rotation-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Creates the file, last modified `age` ago.
+    fn create_aged(path: &Path, age: Duration) {
+        let file = fs::File::create(path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    /// Returns the local time at the given date and time.
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn period_boundaries() {
+        let now = local(2026, 10, 16, 13, 59);
+        assert_eq!(RotationPeriod::Hourly.next_rollover(&now), local(2026, 10, 16, 14, 0));
+        assert_eq!(RotationPeriod::Daily.next_rollover(&now), local(2026, 10, 17, 0, 0));
+        // 2026-10-16 is a Friday
+        assert_eq!(RotationPeriod::Weekly.next_rollover(&now), local(2026, 10, 19, 0, 0));
+
+        // A period starts exactly at its boundary
+        let midnight = local(2026, 10, 17, 0, 0);
+        assert_eq!(
+            RotationPeriod::Hourly.next_rollover(&midnight),
+            local(2026, 10, 17, 1, 0)
+        );
+        assert_eq!(
+            RotationPeriod::Daily.next_rollover(&midnight),
+            local(2026, 10, 18, 0, 0)
+        );
+    }
+
+    #[test]
+    fn dated_paths() {
+        let path = Path::new("logs/app.log");
+        let now = local(2026, 10, 16, 13, 30);
+        let dated = |period| dated_path(path, period, None, &now).unwrap();
+        assert_eq!(dated(RotationPeriod::Hourly), Path::new("logs/app-2026-10-16-13.log"));
+        assert_eq!(dated(RotationPeriod::Daily), Path::new("logs/app-2026-10-16.log"));
+        assert_eq!(dated(RotationPeriod::Weekly), Path::new("logs/app-2026-W42.log"));
+
+        let custom = dated_path(path, RotationPeriod::Daily, Some("%Y%m%d.txt"), &now).unwrap();
+        assert_eq!(custom, Path::new("logs/20261016.txt"));
+        assert!(dated_path(path, RotationPeriod::Daily, Some("%Q.log"), &now).is_err());
+    }
+
+    #[test]
+    fn dated_file_names() {
+        let daily = default_pattern(Path::new("app.log"), RotationPeriod::Daily);
+        assert!(is_dated_file("app-2026-10-16.log", &daily));
+        assert!(is_dated_file("app-2026-10-16.log.gz", &daily));
+        assert!(is_dated_file("app-2026-10-16.log.2", &daily));
+        assert!(!is_dated_file("app-errors.log", &daily));
+        assert!(!is_dated_file("app-2026-10-16-extra.log", &daily));
+        assert!(!is_dated_file("app.log", &daily));
+
+        let weekly = default_pattern(Path::new("app.log"), RotationPeriod::Weekly);
+        assert!(is_dated_file("app-2026-W42.log", &weekly));
+        assert!(!is_dated_file("app-2026-10-16.log", &weekly));
+    }
+
+    #[test]
+    fn remove_expired_keeps_unrelated_files() {
+        let dir = temp_dir("expired");
+        let old = Duration::from_secs(90 * 24 * 60 * 60);
+        for name in [
+            "app-2026-01-01.log",
+            "app-2026-01-02.log.gz",
+            "app-errors.log",
+            "app-backup.log.gz",
+            "app.log",
+            "notes.txt",
+            "app-2026-10-16.log",
+        ] {
+            create_aged(&dir.join(name), old);
+        }
+        create_aged(&dir.join("app-2026-10-15.log"), Duration::ZERO);
+
+        let current = dir.join("app-2026-10-16.log");
+        let max_age = Duration::from_secs(30 * 24 * 60 * 60);
+        remove_expired(&dir.join("app.log"), RotationPeriod::Daily, None, max_age, &current).unwrap();
+
+        let mut left: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                "app-2026-10-15.log",
+                "app-2026-10-16.log",
+                "app-backup.log.gz",
+                "app-errors.log",
+                "app.log",
+                "notes.txt"
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
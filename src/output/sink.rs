@@ -0,0 +1,195 @@
+//! This module defines the `Sink` trait, implemented by every output of the log records.
+//!
+//! # Traits
+//!
+//! - `Sink`: Receives the log records enabled for a logger.
+//!
+//! # Structures
+//!
+//! - `ConsoleSink`: Prints the records to stdout, to stderr, or splits them between both, with colors.
+//! - `FileSink`: Writes the records to a log file, as text or JSON, with its rotation policy.
+//!
+//! # Enums
+//!
+//! - `ConsoleOutput`: The console streams a `ConsoleSink` prints to.
+//!
+//! A `LogDestination` holds a list of sinks: the console and file outputs configured with
+//! `log_to_stdout`, `log_to_stderr` and `log_to_file` are sinks like any other, and custom sinks are attached by
+//! name with `add_sink`, to the default logger or to a module logger. A record is handed to every
+//! sink of the logger it is logged from.
+//!
+//! ```
+//! use std::sync::Mutex;
+//!
+//! use log_x::LogMetadata;
+//! use log_x::Logger;
+//! use log_x::loggers::global_logger::DefaultLoggerTrait;
+//! use log_x::loggers::log_levels::LogLevel;
+//! use log_x::output::sink::Sink;
+//!
+//! /// Keeps the messages of the records in memory.
+//! #[derive(Default)]
+//! struct Memory(Mutex<Vec<String>>);
+//!
+//! impl Sink for Memory {
+//!     fn log(&self, metadata: &LogMetadata) {
+//!         self.0.lock().unwrap().push(metadata.message().to_string());
+//!     }
+//! }
+//!
+//! Logger::set_log_level(LogLevel::Info);
+//! Logger::add_sink("memory", Memory::default());
+//! log_x::log_info!("kept in memory");
+//! Logger::remove_sink("memory");
+//! ```
+use std::sync::Arc;
+
+use crate::LogMetadata;
+use crate::loggers::log_levels::LogLevel;
+use crate::output::file_writer;
+use crate::output::format::{LogFormat, to_json};
+use crate::output::logdest::{format_text, log_to_stderr, log_to_stdout, paranoia_enabled};
+use crate::output::rotation::RotationPolicy;
+
+/// An output of the log records.
+///
+/// The sinks are shared by all the threads logging, and called from the writer thread in the
+/// asynchronous mode, so they must be `Send` and `Sync`. A sink should never panic: errors should
+/// be handled or reported, e.g. through [`crate::output::error::report`].
+pub trait Sink: Send + Sync {
+    /// Writes a log record enabled for the logger the sink is attached to.
+    fn log(&self, metadata: &LogMetadata);
+
+    /// Writes the buffered records, if any. Called by [`Logger::flush`](crate::Logger::flush).
+    fn flush(&self) {}
+}
+
+/// A shared sink, e.g. to keep a handle on a sink attached to a logger.
+impl<S: Sink + ?Sized> Sink for Arc<S> {
+    fn log(&self, metadata: &LogMetadata) {
+        (**self).log(metadata);
+    }
+
+    fn flush(&self) {
+        (**self).flush();
+    }
+}
+
+/// The console streams a `ConsoleSink` prints to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub enum ConsoleOutput {
+    /// Every record is printed to stdout.
+    #[default]
+    Stdout,
+    /// Every record is printed to stderr, keeping stdout for the output of the application.
+    Stderr,
+    /// `Warn` and `Error` records are printed to stderr, the other ones to stdout.
+    Split,
+}
+
+/// Prints the log records to the console, with colors.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConsoleSink {
+    output: ConsoleOutput,
+}
+
+impl ConsoleSink {
+    /// Creates a sink printing to the given console streams.
+    pub fn new(output: ConsoleOutput) -> Self {
+        ConsoleSink { output }
+    }
+
+    /// Returns the console streams the sink prints to.
+    pub fn output(&self) -> ConsoleOutput {
+        self.output
+    }
+}
+
+impl Sink for ConsoleSink {
+    fn log(&self, metadata: &LogMetadata) {
+        match self.output {
+            ConsoleOutput::Stdout => log_to_stdout(metadata),
+            ConsoleOutput::Stderr => log_to_stderr(metadata),
+            ConsoleOutput::Split if metadata.level() <= LogLevel::Warn => log_to_stderr(metadata),
+            ConsoleOutput::Split => log_to_stdout(metadata),
+        }
+    }
+}
+
+/// Writes the log records to a file, through its shared handle, see [`crate::output::file_writer`].
+/// Records are written to disk right away, unless buffering is enabled: then only `Warn` and `Error`
+/// records are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileSink {
+    path: String,
+    format: LogFormat,
+    rotation: RotationPolicy,
+    compression: bool,
+}
+
+impl FileSink {
+    /// Creates a sink writing text lines to the file at `path`, without rotation.
+    pub fn new(path: impl Into<String>) -> Self {
+        FileSink {
+            path: path.into(),
+            format: LogFormat::Text,
+            rotation: RotationPolicy::Never,
+            compression: false,
+        }
+    }
+
+    /// Sets the format of the lines written to the file.
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the rotation policy of the file.
+    pub fn with_rotation(mut self, rotation: RotationPolicy) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets whether the rotated files are compressed with gzip.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the format of the lines written to the file.
+    pub fn format(&self) -> LogFormat {
+        self.format
+    }
+
+    /// Returns the rotation policy of the file.
+    pub fn rotation(&self) -> &RotationPolicy {
+        &self.rotation
+    }
+
+    /// Returns whether the rotated files are compressed with gzip.
+    pub fn compression(&self) -> bool {
+        self.compression
+    }
+}
+
+impl Sink for FileSink {
+    fn log(&self, metadata: &LogMetadata) {
+        let payload = match self.format {
+            LogFormat::Text => format_text(metadata),
+            LogFormat::Json => to_json(metadata, paranoia_enabled(metadata)),
+        };
+        file_writer::write_line(
+            &self.path,
+            &payload,
+            metadata.level() <= LogLevel::Warn,
+            &self.rotation,
+            self.compression,
+        );
+    }
+}
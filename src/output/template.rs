@@ -0,0 +1,292 @@
+//! This module parses the line templates of the console and text file outputs.
+//!
+//! A template is a text with placeholders between braces, replaced by the values of each record:
+//!
+//! - `{timestamp}`: the timestamp of the record.
+//! - `{level}`: the level, e.g. `INFO`, colorized on the console.
+//! - `{module}`: the module the record was logged from, gray on the console.
+//! - `{file}` and `{line}`: the source location of the record.
+//! - `{message}`: the message.
+//! - `{fields}`: the structured fields, as space separated `key=value` pairs.
+//!
+//! A placeholder can be followed by a format specifier, `{name:[align][width][.max]}`: the value is
+//! truncated to `max` characters, then padded to `width` characters, aligned to the left (`<`, the
+//! default), the right (`>`) or the center (`^`). Use `{{` and `}}` for literal braces.
+//!
+//! The template is parsed once into a `Template`, and set on the default logger or on a module
+//! logger with `set_template` and `set_mod_template`. It replaces the built-in layout of the console
+//! and text file lines, paranoia included: use `{file}` and `{line}` to show the source location.
+//! JSON lines are not affected.
+//!
+//! ```
+//! use log_x::LogMetadata;
+//! use log_x::loggers::log_levels::LogLevel;
+//! use log_x::output::template::Template;
+//!
+//! let template: Template = "{timestamp} {level:>5} {module:.8} {file}:{line} - {message}".parse().unwrap();
+//! let metadata = LogMetadata::new("2026-10-16 13:00:00", LogLevel::Info, "src/db.rs", "my_crate::db", 42, "connected");
+//! assert_eq!(
+//!     template.render(&metadata, false),
+//!     "2026-10-16 13:00:00  INFO my_crate src/db.rs:42 - connected"
+//! );
+//!
+//! // unknown placeholders are reported
+//! assert!("{timestamp} {lvl}".parse::<Template>().is_err());
+//! ```
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::LogMetadata;
+use crate::terminal::colors::Colorize;
+
+/// A parsed line template.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Template {
+    source: String,
+    pieces: Vec<Piece>,
+}
+
+/// A literal text or a placeholder of a template.
+#[derive(Clone, Debug, PartialEq)]
+enum Piece {
+    Literal(String),
+    Placeholder {
+        value: Value,
+        align: Align,
+        width: usize,
+        max: Option<usize>,
+    },
+}
+
+/// The record value replacing a placeholder.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Value {
+    Timestamp,
+    Level,
+    Module,
+    File,
+    Line,
+    Message,
+    Fields,
+}
+
+/// The alignment of a padded value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// The error returned when a template cannot be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateError {
+    /// A placeholder is not one of the known placeholders.
+    UnknownPlaceholder {
+        /// The byte offset of the placeholder in the template.
+        position: usize,
+        /// The unknown placeholder.
+        name: String,
+    },
+    /// The format specifier of a placeholder is not `[align][width][.max]`.
+    InvalidSpecifier {
+        /// The byte offset of the placeholder in the template.
+        position: usize,
+        /// The invalid specifier.
+        specifier: String,
+    },
+    /// A brace is not closed or not escaped.
+    UnmatchedBrace {
+        /// The byte offset of the brace in the template.
+        position: usize,
+    },
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownPlaceholder { position, name } => write!(
+                f,
+                "unknown placeholder \"{}\" at {}, expected one of: timestamp, level, module, file, line, message, fields",
+                name, position
+            ),
+            TemplateError::InvalidSpecifier { position, specifier } => write!(
+                f,
+                "invalid format specifier \"{}\" at {}, expected [<|>|^][width][.max]",
+                specifier, position
+            ),
+            TemplateError::UnmatchedBrace { position } => {
+                write!(
+                    f,
+                    "unmatched brace at {}, use {{{{ and }}}} for literal braces",
+                    position
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl Template {
+    /// Parses a template.
+    pub fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().is_some_and(|&(_, next)| next == '{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().is_some_and(|&(_, next)| next == '}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(TemplateError::UnmatchedBrace { position }),
+                '{' => {
+                    let Some(end) = template[position..].find('}').map(|end| position + end) else {
+                        return Err(TemplateError::UnmatchedBrace { position });
+                    };
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(parse_placeholder(&template[position + 1..end], position)?);
+                    while chars.next_if(|&(index, _)| index <= end).is_some() {}
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Template {
+            source: template.to_string(),
+            pieces,
+        })
+    }
+
+    /// Returns the text the template was parsed from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Renders the metadata as a line, colorizing the level and the module if `colors` is set and
+    /// colors are enabled, see [`crate::terminal::colors`].
+    pub fn render(&self, metadata: &LogMetadata, colors: bool) -> String {
+        let mut line = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(literal) => line.push_str(literal),
+                Piece::Placeholder {
+                    value,
+                    align,
+                    width,
+                    max,
+                } => {
+                    let text = pad(truncate(value.text(metadata), *max), *align, *width);
+                    match (colors, value) {
+                        (true, Value::Level) => line.push_str(&text.colorize(metadata.level().color())),
+                        (true, Value::Module) => line.push_str(&text.gray()),
+                        _ => line.push_str(&text),
+                    }
+                }
+            }
+        }
+        line
+    }
+}
+
+impl Value {
+    /// Returns the plain text of the value for the record.
+    fn text(&self, metadata: &LogMetadata) -> String {
+        match self {
+            Value::Timestamp => metadata.timestamp(),
+            Value::Level => metadata.level().to_string(),
+            Value::Module => metadata.module().to_string(),
+            Value::File => metadata.file().to_string(),
+            Value::Line => metadata.line().to_string(),
+            Value::Message => metadata.message().to_string(),
+            Value::Fields => metadata
+                .fields()
+                .iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        Template::parse(template)
+    }
+}
+
+/// Parses the content of a placeholder, `name[:specifier]`, found at `position`.
+fn parse_placeholder(content: &str, position: usize) -> Result<Piece, TemplateError> {
+    let (name, specifier) = content.split_once(':').unwrap_or((content, ""));
+    let value = match name.trim() {
+        "timestamp" => Value::Timestamp,
+        "level" => Value::Level,
+        "module" => Value::Module,
+        "file" => Value::File,
+        "line" => Value::Line,
+        "message" => Value::Message,
+        "fields" => Value::Fields,
+        _ => {
+            return Err(TemplateError::UnknownPlaceholder {
+                position,
+                name: name.to_string(),
+            });
+        }
+    };
+
+    let invalid = || TemplateError::InvalidSpecifier {
+        position,
+        specifier: specifier.to_string(),
+    };
+    let (align, rest) = match specifier.chars().next() {
+        Some('<') => (Align::Left, &specifier[1..]),
+        Some('>') => (Align::Right, &specifier[1..]),
+        Some('^') => (Align::Center, &specifier[1..]),
+        _ => (Align::Left, specifier),
+    };
+    let (width, max) = match rest.split_once('.') {
+        Some((width, max)) => (width, Some(max.parse::<usize>().map_err(|_| invalid())?)),
+        None => (rest, None),
+    };
+    let width = match width {
+        "" => 0,
+        width => width.parse::<usize>().map_err(|_| invalid())?,
+    };
+
+    Ok(Piece::Placeholder {
+        value,
+        align,
+        width,
+        max,
+    })
+}
+
+/// Truncates the text to `max` characters.
+fn truncate(text: String, max: Option<usize>) -> String {
+    match max {
+        Some(max) if text.chars().count() > max => text.chars().take(max).collect(),
+        _ => text,
+    }
+}
+
+/// Pads the text with spaces to `width` characters.
+fn pad(text: String, align: Align, width: usize) -> String {
+    match align {
+        Align::Left => format!("{:<width$}", text),
+        Align::Right => format!("{:>width$}", text),
+        Align::Center => format!("{:^width$}", text),
+    }
+}
@@ -0,0 +1,146 @@
+//! This module defines the timestamps of the log records and how the destinations format them.
+//!
+//! # Structures
+//!
+//! - `Timestamp`: The instant a record was created, captured as a `chrono::DateTime`.
+//!
+//! # Enums
+//!
+//! - `TimestampFormat`: How a destination formats the timestamps: the precisions of the `timestamp!`
+//!   macro, RFC 3339, or a custom `chrono` format.
+//!
+//! The instant is only formatted when the record is written, with the format and the time zone of
+//! the destination, set with `set_timestamp_format` and `set_timestamp_utc` on the default logger
+//! or on a module logger. Records created with a preformatted text, e.g. `timestamp!()`, keep their
+//! text whatever the format of the destination.
+//!
+//! ```
+//! use chrono::{Local, TimeZone};
+//! use log_x::output::timestamp::{Timestamp, TimestampFormat};
+//!
+//! let time = Local.with_ymd_and_hms(2026, 10, 16, 13, 0, 0).unwrap();
+//! let timestamp = Timestamp::from(time);
+//! assert_eq!(timestamp.format(&TimestampFormat::Seconds, false), "2026-10-16 13:00:00");
+//! assert_eq!(timestamp.format(&TimestampFormat::Millis, false), "2026-10-16 13:00:00.000");
+//! assert_eq!(timestamp.time(), time);
+//! ```
+use std::fmt::Write;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+
+/// How a destination formats the timestamps of the records.
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd)]
+pub enum TimestampFormat {
+    /// Seconds precision, e.g. `2026-10-16 13:00:00`, as `timestamp!()`.
+    #[default]
+    Seconds,
+    /// Milliseconds precision, e.g. `2026-10-16 13:00:00.123`, as `timestamp!(millis)`.
+    Millis,
+    /// Microseconds precision, as `timestamp!(micros)`.
+    Micros,
+    /// Nanoseconds precision, as `timestamp!(nanos)`.
+    Nanos,
+    /// RFC 3339 with the time zone offset, e.g. `2026-10-16T13:00:00.123456+02:00`.
+    Rfc3339,
+    /// A custom `chrono` format, as `timestamp!("%H:%M:%S")`.
+    Custom(String),
+}
+
+impl TimestampFormat {
+    /// Returns the `chrono` format of the timestamps, `None` for RFC 3339.
+    pub fn pattern(&self) -> Option<&str> {
+        match self {
+            TimestampFormat::Seconds => Some("%Y-%m-%d %H:%M:%S"),
+            TimestampFormat::Millis => Some("%Y-%m-%d %H:%M:%S%.3f"),
+            TimestampFormat::Micros => Some("%Y-%m-%d %H:%M:%S%.6f"),
+            TimestampFormat::Nanos => Some("%Y-%m-%d %H:%M:%S%.9f"),
+            TimestampFormat::Rfc3339 => None,
+            TimestampFormat::Custom(pattern) => Some(pattern),
+        }
+    }
+}
+
+/// The instant a log record was created.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timestamp {
+    time: DateTime<Local>,
+    /// The preformatted text of the records created with a `String` timestamp.
+    text: Option<String>,
+}
+
+impl Timestamp {
+    /// Captures the current instant.
+    pub fn now() -> Self {
+        Timestamp::from(Local::now())
+    }
+
+    /// Returns the instant the record was created.
+    pub fn time(&self) -> DateTime<Local> {
+        self.time
+    }
+
+    /// Formats the timestamp, in UTC if `utc` is set, in local time otherwise. A preformatted
+    /// timestamp is returned as is.
+    pub fn format(&self, format: &TimestampFormat, utc: bool) -> String {
+        if let Some(text) = &self.text {
+            return text.clone();
+        }
+
+        let Some(pattern) = format.pattern() else {
+            return match utc {
+                true => self
+                    .time
+                    .with_timezone(&Utc)
+                    .to_rfc3339_opts(SecondsFormat::Micros, true),
+                false => self.time.to_rfc3339_opts(SecondsFormat::Micros, false),
+            };
+        };
+
+        // An invalid custom format is reported in the text, never with a panic
+        let mut text = String::new();
+        let written = match utc {
+            true => write!(text, "{}", self.time.with_timezone(&Utc).format(pattern)),
+            false => write!(text, "{}", self.time.format(pattern)),
+        };
+        match written {
+            Ok(()) => text,
+            Err(_) => format!("<invalid timestamp format \"{}\">", pattern),
+        }
+    }
+}
+
+impl From<DateTime<Local>> for Timestamp {
+    fn from(time: DateTime<Local>) -> Self {
+        Timestamp { time, text: None }
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(time: DateTime<Utc>) -> Self {
+        Timestamp::from(time.with_timezone(&Local))
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        Timestamp::from(DateTime::<Local>::from(time))
+    }
+}
+
+/// A preformatted timestamp, e.g. from `timestamp!()`, captured now.
+impl From<String> for Timestamp {
+    fn from(text: String) -> Self {
+        Timestamp {
+            time: Local::now(),
+            text: Some(text),
+        }
+    }
+}
+
+/// A preformatted timestamp, captured now.
+impl From<&str> for Timestamp {
+    fn from(text: &str) -> Self {
+        Timestamp::from(text.to_string())
+    }
+}
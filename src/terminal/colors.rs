@@ -3,6 +3,8 @@
 //! # Enums
 //!
 //! - `Color`: Represents various colors that can be used to colorize terminal text.
+//! - `ColorMode`: Whether the `Colorize` methods emit ANSI escape codes: auto, always or never.
+//! - `Stream`: The console stream the colorized text is written to.
 //!
 //! # Traits
 //!
@@ -27,7 +29,112 @@
 //! - `black`: Colors the text black.
 //!
 //! Each method returns a `String` with the text wrapped in the appropriate ANSI escape codes for the specified color.
+//!
+//! # Color Mode
+//!
+//! When colors are disabled, each method returns the plain text. With `ColorMode::Auto`, the default,
+//! colors are enabled if the stream the text is written to is a terminal, unless the `NO_COLOR`
+//! environment variable is set to a non empty value or `TERM` is `dumb`. `FORCE_COLOR` set to a non
+//! empty value enables colors even when the stream is not a terminal, or disables them if set to `0`
+//! or `false`. The environment and the streams are checked once, on the first colorized text.
+//!
+//! The text is assumed to be written to stdout, unless written from [`with_stream`].
+//!
+//! ```
+//! use log_x::terminal::colors::{ColorMode, Colorize, set_color_mode};
+//!
+//! set_color_mode(ColorMode::Never);
+//! assert_eq!("plain".red(), "plain");
+//!
+//! set_color_mode(ColorMode::Always);
+//! assert_eq!("red".red(), "\x1b[31mred\x1b[0m");
+//! ```
+use std::cell::Cell;
 use std::fmt::{Debug, Display};
+use std::io::{self, IsTerminal};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The color mode, `ColorMode` as `u8`.
+static COLOR_MODE: AtomicU8 = AtomicU8::new(ColorMode::Auto as u8);
+
+/// Whether stdout gets colors with `ColorMode::Auto`.
+static STDOUT_COLORS: LazyLock<bool> = LazyLock::new(|| detect_colors(io::stdout().is_terminal()));
+
+/// Whether stderr gets colors with `ColorMode::Auto`.
+static STDERR_COLORS: LazyLock<bool> = LazyLock::new(|| detect_colors(io::stderr().is_terminal()));
+
+thread_local! {
+    /// The stream the text colorized on this thread is written to.
+    static STREAM: Cell<Stream> = const { Cell::new(Stream::Stdout) };
+}
+
+/// Whether the `Colorize` methods emit ANSI escape codes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors if the stream is a terminal, honouring `NO_COLOR` and `FORCE_COLOR`.
+    #[default]
+    Auto,
+    /// Always colors.
+    Always,
+    /// Never colors.
+    Never,
+}
+
+/// The console stream the colorized text is written to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Stream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+/// Sets the color mode of the `Colorize` methods.
+pub fn set_color_mode(mode: ColorMode) {
+    COLOR_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// Returns the color mode of the `Colorize` methods.
+pub fn color_mode() -> ColorMode {
+    match COLOR_MODE.load(Ordering::Relaxed) {
+        1 => ColorMode::Always,
+        2 => ColorMode::Never,
+        _ => ColorMode::Auto,
+    }
+}
+
+/// Returns whether the text written to `stream` gets colors.
+pub fn colors_enabled(stream: Stream) -> bool {
+    match color_mode() {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto => match stream {
+            Stream::Stdout => *STDOUT_COLORS,
+            Stream::Stderr => *STDERR_COLORS,
+        },
+    }
+}
+
+/// Runs `f`, the text it colorizes being written to `stream`.
+pub fn with_stream<R>(stream: Stream, f: impl FnOnce() -> R) -> R {
+    let previous = STREAM.replace(stream);
+    let result = f();
+    STREAM.set(previous);
+    result
+}
+
+/// Returns whether a stream gets colors with `ColorMode::Auto`, from the environment conventions
+/// and whether it is a terminal.
+fn detect_colors(is_terminal: bool) -> bool {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    if let Some(force) = var("FORCE_COLOR") {
+        return force != "0" && force != "false";
+    }
+    if var("NO_COLOR").is_some() || var("TERM").is_some_and(|term| term == "dumb") {
+        return false;
+    }
+    is_terminal
+}
 
 // Define an enum to represent colors
 /// Represents various colors that can be used to colorize terminal text.
@@ -66,9 +173,13 @@ impl Color {
 // Define a trait that extends the Display and Debug trait with color methods
 /// Extends the `Display` and `Debug` traits with methods to colorize text.
 /// Each method returns a `String` with the text wrapped in the appropriate ANSI escape codes for the specified color and resets the color at the end.
+/// Returns the plain text when colors are disabled, see [`ColorMode`].
 pub trait Colorize: Display + Debug {
     fn colorize(&self, color: Color) -> String {
-        format!("{}{}{}", color.to_ansi_code(), self, Color::Reset.to_ansi_code())
+        match colors_enabled(STREAM.get()) {
+            true => format!("{}{}{}", color.to_ansi_code(), self, Color::Reset.to_ansi_code()),
+            false => self.to_string(),
+        }
     }
 
     fn red(&self) -> String {